@@ -2,6 +2,7 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
+use error::{Error, RouterError};
 use handler::Reply;
 use router::{Router, Pattern};
 use resource::Resource;
@@ -20,16 +21,30 @@ pub struct HttpApplication<S=()> {
     middlewares: Rc<Vec<Box<Middleware<S>>>>,
 }
 
+type ExpectHandler<S> = Box<Fn(&mut HttpRequest<S>) -> Result<(), Error>>;
+
 pub(crate) struct Inner<S> {
     default: Resource<S>,
     router: Router,
     resources: Vec<Resource<S>>,
+    expect: Option<ExpectHandler<S>>,
 }
 
 impl<S: 'static> PipelineHandler<S> for Inner<S> {
 
     fn handle(&mut self, mut req: HttpRequest<S>) -> Reply {
-        if let Some(idx) = self.router.recognize(&mut req) {
+        let idx = self.router.recognize(&mut req);
+
+        // reject request before client sends payload
+        if let Some(ref expect) = self.expect {
+            if let Ok(true) = req.expect_continue() {
+                if let Err(err) = expect(&mut req) {
+                    return Reply::response(err)
+                }
+            }
+        }
+
+        if let Some(idx) = idx {
             self.resources[idx].handle(req.clone(), Some(&mut self.default))
         } else {
             self.default.handle(req, None)
@@ -72,9 +87,10 @@ struct ApplicationParts<S> {
     prefix: String,
     settings: ServerSettings,
     default: Resource<S>,
-    resources: HashMap<Pattern, Option<Resource<S>>>,
-    external: HashMap<String, Pattern>,
+    resources: Vec<(String, Resource<S>)>,
+    external: HashMap<String, String>,
     middlewares: Vec<Box<Middleware<S>>>,
+    expect: Option<ExpectHandler<S>>,
 }
 
 /// Structure that follows the builder pattern for building `Application` structs.
@@ -93,9 +109,10 @@ impl Application<()> {
                 prefix: "/".to_owned(),
                 settings: ServerSettings::default(),
                 default: Resource::default_not_found(),
-                resources: HashMap::new(),
+                resources: Vec::new(),
                 external: HashMap::new(),
                 middlewares: Vec::new(),
+                expect: None,
             })
         }
     }
@@ -121,9 +138,10 @@ impl<S> Application<S> where S: 'static {
                 prefix: "/".to_owned(),
                 settings: ServerSettings::default(),
                 default: Resource::default_not_found(),
-                resources: HashMap::new(),
+                resources: Vec::new(),
                 external: HashMap::new(),
                 middlewares: Vec::new(),
+                expect: None,
             })
         }
     }
@@ -208,12 +226,7 @@ impl<S> Application<S> where S: 'static {
             let mut resource = Resource::default();
             f(&mut resource);
 
-            let pattern = Pattern::new(resource.get_name(), path);
-            if parts.resources.contains_key(&pattern) {
-                panic!("Resource {:?} is registered.", path);
-            }
-
-            parts.resources.insert(pattern, Some(resource));
+            parts.resources.push((path.to_owned(), resource));
         }
         self
     }
@@ -261,8 +274,7 @@ impl<S> Application<S> where S: 'static {
             if parts.external.contains_key(name.as_ref()) {
                 panic!("External resource {:?} is registered.", name.as_ref());
             }
-            parts.external.insert(
-                String::from(name.as_ref()), Pattern::new(name.as_ref(), url.as_ref()));
+            parts.external.insert(String::from(name.as_ref()), String::from(url.as_ref()));
         }
         self
     }
@@ -276,32 +288,107 @@ impl<S> Application<S> where S: 'static {
         self
     }
 
+    /// Check requests with `Expect: 100-continue` header before payload is sent.
+    ///
+    /// `100 Continue` is sent only when request payload gets read, so
+    /// returning error from this function rejects upload with error response
+    /// and client does not send payload at all.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// use actix_web::*;
+    ///
+    /// fn main() {
+    ///     let app = Application::new()
+    ///         .expect(|req| {
+    ///             if req.headers().contains_key("authorization") {
+    ///                 Ok(())
+    ///             } else {
+    ///                 Err(error::ErrorUnauthorized("unauthorized").into())
+    ///             }
+    ///         })
+    ///         .resource("/upload", |r| r.f(|_| httpcodes::HTTPOk))
+    ///         .finish();
+    /// }
+    /// ```
+    pub fn expect<F>(mut self, f: F) -> Application<S>
+        where F: Fn(&mut HttpRequest<S>) -> Result<(), Error> + 'static
+    {
+        self.parts.as_mut().expect("Use after finish").expect = Some(Box::new(f));
+        self
+    }
+
     /// Finish application configuration and create HttpHandler object
+    ///
+    /// Panics if path pattern is wrong or resource can never be matched,
+    /// use `try_finish()` to handle errors.
     pub fn finish(&mut self) -> HttpApplication<S> {
+        match self.try_finish() {
+            Ok(app) => app,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Finish application configuration and create HttpHandler object
+    ///
+    /// Returns error if path pattern is wrong, if resource is shadowed
+    /// by previously registered resource or if resource name is used twice.
+    ///
+    /// Shadowing is detected for identical patterns, for patterns registered
+    /// after `{tail:.*}` segment with the same parent path, and for custom regex
+    /// segments, like `{id:\d+}`, which can only match text of `{name}` segment
+    /// with the same parent path. Other regexes, like `{tail:.+}`, are not
+    /// compared with each other.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// use actix_web::*;
+    /// use actix_web::error::RouterError;
+    ///
+    /// fn main() {
+    ///     let res = Application::new()
+    ///         .resource("/user/{id}", |r| r.f(|_| httpcodes::HTTPOk))
+    ///         .resource("/user/{name}", |r| r.f(|_| httpcodes::HTTPOk))
+    ///         .try_finish();
+    ///     match res {
+    ///         Err(RouterError::Duplicate(first, second)) => {
+    ///             assert_eq!(first, "/user/{id}");
+    ///             assert_eq!(second, "/user/{name}");
+    ///         }
+    ///         _ => unreachable!(),
+    ///     }
+    /// }
+    /// ```
+    pub fn try_finish(&mut self) -> Result<HttpApplication<S>, RouterError> {
         let parts = self.parts.take().expect("Use after finish");
         let prefix = parts.prefix.trim().trim_right_matches('/');
 
-        let mut resources = parts.resources;
-        for (_, pattern) in parts.external {
-            resources.insert(pattern, None);
+        let mut resources = Vec::new();
+        for (path, resource) in parts.resources {
+            let pattern = Pattern::try_new(resource.get_name(), &path)?;
+            resources.push((pattern, Some(resource)));
+        }
+        for (name, url) in parts.external {
+            resources.push((Pattern::try_new(&name, &url)?, None));
         }
 
-        let (router, resources) = Router::new(prefix, parts.settings, resources);
+        let (router, resources) = Router::new(prefix, parts.settings, resources)?;
 
         let inner = Rc::new(RefCell::new(
             Inner {
                 default: parts.default,
                 router: router.clone(),
-                resources: resources }
+                resources: resources,
+                expect: parts.expect }
         ));
 
-        HttpApplication {
+        Ok(HttpApplication {
             state: Rc::new(parts.state),
             prefix: prefix.to_owned(),
             inner: inner,
             router: router.clone(),
             middlewares: Rc::new(parts.middlewares),
-        }
+        })
     }
 }
 
@@ -346,8 +433,9 @@ impl<S: 'static> Iterator for Application<S> {
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
-    use http::{Method, Version, Uri, HeaderMap, StatusCode};
+    use http::{header, Method, Version, Uri, HeaderMap, StatusCode};
     use super::*;
+    use error;
     use test::TestRequest;
     use httprequest::HttpRequest;
     use httpcodes;
@@ -415,4 +503,56 @@ mod tests {
         let resp = app.handle(req);
         assert!(resp.is_err());
     }
+
+    #[test]
+    fn test_expect() {
+        let mut app = Application::new()
+            .expect(|req| {
+                if req.content_type() == "text/plain" {
+                    Ok(())
+                } else {
+                    Err(error::ErrorBadRequest("content type").into())
+                }
+            })
+            .resource("/test", |r| r.h(httpcodes::HTTPOk))
+            .finish();
+
+        let req = TestRequest::with_uri("/test")
+            .header(header::EXPECT, "100-continue")
+            .header(header::CONTENT_TYPE, "text/plain").finish();
+        let resp = app.run(req);
+        assert_eq!(resp.as_response().unwrap().status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/test")
+            .header(header::EXPECT, "100-continue").finish();
+        let resp = app.run(req);
+        assert_eq!(resp.as_response().unwrap().status(), StatusCode::BAD_REQUEST);
+
+        let req = TestRequest::with_uri("/test").finish();
+        let resp = app.run(req);
+        assert_eq!(resp.as_response().unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_try_finish() {
+        let res = Application::new()
+            .resource("/test/{tail:.*}", |r| r.h(httpcodes::HTTPOk))
+            .resource("/test/{name:[a-z]+}", |r| r.h(httpcodes::HTTPOk))
+            .try_finish();
+        match res {
+            Err(error::RouterError::Shadowed(first, second)) => {
+                assert_eq!(first, "/test/{tail:.*}");
+                assert_eq!(second, "/test/{name:[a-z]+}");
+            }
+            _ => panic!("shadowed resource error is expected"),
+        }
+
+        let res = Application::new()
+            .resource("/test/{name:(}", |r| r.h(httpcodes::HTTPOk))
+            .try_finish();
+        match res {
+            Err(error::RouterError::Pattern(path, _)) => assert_eq!(path, "/test/{name:(}"),
+            _ => panic!("pattern error is expected"),
+        }
+    }
 }
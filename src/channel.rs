@@ -1,7 +1,9 @@
 use std::rc::Rc;
+use std::sync::Arc;
 use std::net::SocketAddr;
 
 use bytes::Bytes;
+use http::Extensions;
 use futures::{Future, Poll, Async};
 use tokio_io::{AsyncRead, AsyncWrite};
 
@@ -9,6 +11,7 @@ use {h1, h2};
 use error::Error;
 use h1writer::Writer;
 use httprequest::HttpRequest;
+use info::{PeerCertificate, ServerName};
 use server::ServerSettings;
 use worker::WorkerSettings;
 
@@ -20,6 +23,12 @@ pub trait HttpHandler: 'static {
     fn handle(&mut self, req: HttpRequest) -> Result<Box<HttpHandlerTask>, HttpRequest>;
 }
 
+impl HttpHandler for Box<HttpHandler> {
+    fn handle(&mut self, req: HttpRequest) -> Result<Box<HttpHandlerTask>, HttpRequest> {
+        (**self).handle(req)
+    }
+}
+
 pub trait HttpHandlerTask {
 
     fn poll_io(&mut self, io: &mut Writer) -> Poll<bool, Error>;
@@ -46,6 +55,74 @@ impl<T: HttpHandler> IntoHttpHandler for T {
     }
 }
 
+/// Connection callback, returns `false` if connection should be closed
+pub(crate) type ConnectHandler = Arc<Fn(&mut Connection) -> bool + Send + Sync>;
+
+/// Accepted connection, passed to `HttpServer::on_connect()` callback
+/// before any request is read.
+pub struct Connection {
+    peer: Option<SocketAddr>,
+    http2: bool,
+    certificate: Option<Rc<PeerCertificate>>,
+    server_name: Option<String>,
+    data: Vec<Box<Fn(&mut Extensions)>>,
+}
+
+impl Connection {
+    pub(crate) fn new(peer: Option<SocketAddr>, http2: bool) -> Connection {
+        Connection { peer: peer, http2: http2,
+                     certificate: None, server_name: None, data: Vec::new() }
+    }
+
+    /// Set tls client certificate and server name
+    #[cfg_attr(not(feature="alpn"), allow(dead_code))]
+    pub(crate) fn tls(mut self, cert: Option<PeerCertificate>, name: Option<String>) -> Self {
+        self.certificate = cert.map(Rc::new);
+        self.server_name = name;
+        self
+    }
+
+    /// Peer socket address. If PROXY protocol is enabled,
+    /// this is client address advertised by proxy.
+    pub fn peer_addr(&self) -> Option<&SocketAddr> {
+        self.peer.as_ref()
+    }
+
+    /// Returns true if http/2 is negotiated with ALPN
+    pub fn http2(&self) -> bool {
+        self.http2
+    }
+
+    /// Verified tls client certificate
+    pub fn peer_certificate(&self) -> Option<&PeerCertificate> {
+        self.certificate.as_ref().map(|cert| cert.as_ref())
+    }
+
+    /// Server name sent by tls client
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_ref().map(|name| name.as_str())
+    }
+
+    /// Attach data to connection. Every request of this connection
+    /// gets a copy of the value in `HttpRequest::extensions()`.
+    pub fn insert<T: Clone + 'static>(&mut self, val: T) {
+        self.data.push(Box::new(move |ext: &mut Extensions| { ext.insert(val.clone()); }));
+    }
+}
+
+/// Data attached to connection by connect callback
+#[derive(Clone, Default)]
+pub(crate) struct ConnectionData(Rc<Vec<Box<Fn(&mut Extensions)>>>);
+
+impl ConnectionData {
+    /// Copy connection data to request extensions
+    pub fn apply(&self, ext: &mut Extensions) {
+        for f in self.0.iter() {
+            f(ext)
+        }
+    }
+}
+
 enum HttpProtocol<T, H>
     where T: AsyncRead + AsyncWrite + 'static, H: HttpHandler + 'static
 {
@@ -58,32 +135,65 @@ pub struct HttpChannel<T, H>
     where T: AsyncRead + AsyncWrite + 'static, H: HttpHandler + 'static
 {
     proto: Option<HttpProtocol<T, H>>,
+    task: Option<usize>,
 }
 
 impl<T, H> HttpChannel<T, H>
     where T: AsyncRead + AsyncWrite + 'static, H: HttpHandler + 'static
 {
-    pub(crate) fn new(h: Rc<WorkerSettings<H>>,
-               io: T, peer: Option<SocketAddr>, http2: bool) -> HttpChannel<T, H>
+    pub(crate) fn new(h: Rc<WorkerSettings<H>>, io: T, mut conn: Connection)
+                      -> HttpChannel<T, H>
     {
+        if let Some(on_connect) = h.connect_handler() {
+            if !on_connect(&mut conn) {
+                trace!("Connection from {:?} is rejected", conn.peer);
+                return HttpChannel { proto: None, task: None }
+            }
+        }
+        // tls info is available in `ConnectionInfo` of every request
+        if let Some(cert) = conn.certificate.take() {
+            conn.insert(cert);
+        }
+        if let Some(name) = conn.server_name.take() {
+            conn.insert(ServerName(name));
+        }
+        let Connection { peer, http2, data, .. } = conn;
+        let data = ConnectionData(Rc::new(data));
+
         h.add_channel();
         if http2 {
             HttpChannel {
                 proto: Some(HttpProtocol::H2(
-                    h2::Http2::new(h, io, peer, Bytes::new()))) }
+                    h2::Http2::new(h, io, peer, data, Bytes::new(), None))),
+                task: None }
         } else {
             HttpChannel {
                 proto: Some(HttpProtocol::H1(
-                    h1::Http1::new(h, io, peer))) }
+                    h1::Http1::new(h, io, peer, data))),
+                task: None }
+        }
+    }
+
+    fn settings(&self) -> Option<&WorkerSettings<H>> {
+        match self.proto {
+            Some(HttpProtocol::H1(ref h1)) => Some(h1.settings()),
+            Some(HttpProtocol::H2(ref h2)) => Some(h2.settings()),
+            None => None,
         }
     }
 }
 
-/*impl<T: 'static, A: 'static, H: 'static> Drop for HttpChannel<T, A, H> {
+impl<T, H> Drop for HttpChannel<T, H>
+    where T: AsyncRead + AsyncWrite + 'static, H: HttpHandler + 'static
+{
     fn drop(&mut self) {
-        println!("Drop http channel");
+        if let Some(id) = self.task.take() {
+            if let Some(settings) = self.settings() {
+                settings.unregister_task(id);
+            }
+        }
     }
-}*/
+}
 
 impl<T, H> Future for HttpChannel<T, H>
     where T: AsyncRead + AsyncWrite + 'static, H: HttpHandler + 'static
@@ -92,6 +202,12 @@ impl<T, H> Future for HttpChannel<T, H>
     type Error = ();
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        // register for server shutdown notification
+        if self.task.is_none() {
+            let id = self.settings().map(|s| s.register_task());
+            self.task = id;
+        }
+
         match self.proto {
             Some(HttpProtocol::H1(ref mut h1)) => {
                 match h1.poll() {
@@ -116,16 +232,17 @@ impl<T, H> Future for HttpChannel<T, H>
                 }
                 return result
             }
-            None => unreachable!(),
+            // rejected by connect callback
+            None => return Err(()),
         }
 
         // upgrade to h2
         let proto = self.proto.take().unwrap();
         match proto {
             HttpProtocol::H1(h1) => {
-                let (h, io, addr, buf) = h1.into_inner();
+                let (h, io, addr, data, buf, upgrade) = h1.into_inner();
                 self.proto = Some(
-                    HttpProtocol::H2(h2::Http2::new(h, io, addr, buf)));
+                    HttpProtocol::H2(h2::Http2::new(h, io, addr, data, buf, upgrade)));
                 self.poll()
             }
             _ => unreachable!()
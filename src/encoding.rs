@@ -17,7 +17,7 @@ use body::{Body, Binary};
 use error::PayloadError;
 use helpers::SharedBytes;
 use httprequest::HttpMessage;
-use httpresponse::HttpResponse;
+use httpresponse::{HttpResponse, Trailers};
 use payload::{PayloadSender, PayloadWriter};
 
 /// Represents supported types of content encodings
@@ -125,12 +125,26 @@ impl PayloadWriter for PayloadType {
         }
     }
 
+    fn set_trailers(&mut self, trailers: HeaderMap) {
+        match *self {
+            PayloadType::Sender(ref mut sender) => sender.set_trailers(trailers),
+            PayloadType::Encoding(ref mut enc) => enc.set_trailers(trailers),
+        }
+    }
+
     fn capacity(&self) -> usize {
         match *self {
             PayloadType::Sender(ref sender) => sender.capacity(),
             PayloadType::Encoding(ref enc) => enc.capacity(),
         }
     }
+
+    fn need_read(&self) -> bool {
+        match *self {
+            PayloadType::Sender(ref sender) => sender.need_read(),
+            PayloadType::Encoding(ref enc) => enc.need_read(),
+        }
+    }
 }
 
 enum Decoder {
@@ -188,6 +202,10 @@ impl PayloadWriter for EncodedPayload {
         self.inner.set_error(err)
     }
 
+    fn set_trailers(&mut self, trailers: HeaderMap) {
+        self.inner.set_trailers(trailers)
+    }
+
     fn feed_eof(&mut self) {
         if self.error {
             return
@@ -333,6 +351,10 @@ impl PayloadWriter for EncodedPayload {
     fn capacity(&self) -> usize {
         self.inner.capacity()
     }
+
+    fn need_read(&self) -> bool {
+        self.inner.need_read()
+    }
 }
 
 pub(crate) struct PayloadEncoder(ContentEncoder);
@@ -428,7 +450,11 @@ impl PayloadEncoder {
                     } else {
                         resp.headers_mut().insert(
                             TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
-                        TransferEncoding::chunked(buf)
+                        let mut transfer = TransferEncoding::chunked(buf);
+                        if let Some(trailers) = resp.trailers() {
+                            transfer.trailers = Some(trailers.clone());
+                        }
+                        transfer
                     }
                 } else if let Some(len) = resp.headers().get(CONTENT_LENGTH) {
                     // Content-Length
@@ -647,6 +673,7 @@ impl ContentEncoder {
 pub(crate) struct TransferEncoding {
     kind: TransferEncodingKind,
     buffer: SharedBytes,
+    trailers: Option<Trailers>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -670,6 +697,7 @@ impl TransferEncoding {
         TransferEncoding {
             kind: TransferEncodingKind::Eof,
             buffer: bytes,
+            trailers: None,
         }
     }
 
@@ -678,6 +706,7 @@ impl TransferEncoding {
         TransferEncoding {
             kind: TransferEncodingKind::Chunked(false),
             buffer: bytes,
+            trailers: None,
         }
     }
 
@@ -686,6 +715,7 @@ impl TransferEncoding {
         TransferEncoding {
             kind: TransferEncodingKind::Length(len),
             buffer: bytes,
+            trailers: None,
         }
     }
 
@@ -715,7 +745,7 @@ impl TransferEncoding {
 
                 if msg.is_empty() {
                     *eof = true;
-                    self.buffer.get_mut().extend_from_slice(b"0\r\n\r\n");
+                    encode_last_chunk(self.buffer.get_mut(), &self.trailers);
                 } else {
                     write!(self.buffer.get_mut(), "{:X}\r\n", msg.len()).unwrap();
                     self.buffer.get_mut().extend_from_slice(msg);
@@ -744,13 +774,27 @@ impl TransferEncoding {
             TransferEncodingKind::Chunked(ref mut eof) => {
                 if !*eof {
                     *eof = true;
-                    self.buffer.get_mut().extend_from_slice(b"0\r\n\r\n");
+                    encode_last_chunk(self.buffer.get_mut(), &self.trailers);
                 }
             },
         }
     }
 }
 
+/// Last chunk with optional trailer headers
+fn encode_last_chunk(buf: &mut BytesMut, trailers: &Option<Trailers>) {
+    buf.extend_from_slice(b"0\r\n");
+    if let Some(ref trailers) = *trailers {
+        for (name, value) in &trailers.take() {
+            buf.extend_from_slice(name.as_str().as_bytes());
+            buf.extend_from_slice(b": ");
+            buf.extend_from_slice(value.as_bytes());
+            buf.extend_from_slice(b"\r\n");
+        }
+    }
+    buf.extend_from_slice(b"\r\n");
+}
+
 impl io::Write for TransferEncoding {
 
     #[inline]
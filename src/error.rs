@@ -140,6 +140,9 @@ pub enum ParseError {
     /// A message head is too large to be reasonable.
     #[fail(display="Message head is too large")]
     TooLarge,
+    /// A request uri is too long.
+    #[fail(display="Uri is too long")]
+    UriTooLong,
     /// A message reached EOF, but is not complete.
     #[fail(display="Message is incomplete")]
     Incomplete,
@@ -147,7 +150,6 @@ pub enum ParseError {
     #[fail(display="Invalid Status provided")]
     Status,
     /// A timeout occurred waiting for an IO event.
-    #[allow(dead_code)]
     #[fail(display="Timeout")]
     Timeout,
     /// An `io::Error` that occurred while trying to read or write to a network stream.
@@ -158,10 +160,20 @@ pub enum ParseError {
     Utf8(#[cause] Utf8Error),
 }
 
-/// Return `BadRequest` for `ParseError`
+/// Return `RequestHeaderFieldsTooLarge` for too large message head,
+/// `UriTooLong` for too long uri, `RequestTimeout` for timeout
+/// and `BadRequest` for other errors
 impl ResponseError for ParseError {
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::new(StatusCode::BAD_REQUEST, Body::Empty)
+        match *self {
+            ParseError::TooLarge =>
+                HttpResponse::new(StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE, Body::Empty),
+            ParseError::UriTooLong =>
+                HttpResponse::new(StatusCode::URI_TOO_LONG, Body::Empty),
+            ParseError::Timeout =>
+                HttpResponse::new(StatusCode::REQUEST_TIMEOUT, Body::Empty),
+            _ => HttpResponse::new(StatusCode::BAD_REQUEST, Body::Empty),
+        }
     }
 }
 
@@ -210,6 +222,9 @@ pub enum PayloadError {
     /// Http2 error
     #[fail(display="{}", _0)]
     Http2(#[cause] Http2Error),
+    /// Payload is received too slowly
+    #[fail(display="Payload receive timeout")]
+    Timeout,
 }
 
 impl From<IoError> for PayloadError {
@@ -218,8 +233,17 @@ impl From<IoError> for PayloadError {
     }
 }
 
-/// `InternalServerError` for `PayloadError`
-impl ResponseError for PayloadError {}
+/// `RequestTimeout` for payload timeout and `InternalServerError`
+/// for other `PayloadError`
+impl ResponseError for PayloadError {
+    fn error_response(&self) -> HttpResponse {
+        match *self {
+            PayloadError::Timeout =>
+                HttpResponse::new(StatusCode::REQUEST_TIMEOUT, Body::Empty),
+            _ => HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR, Body::Empty),
+        }
+    }
+}
 
 /// Return `BadRequest` for `cookie::ParseError`
 impl ResponseError for cookie::ParseError {
@@ -480,6 +504,20 @@ impl From<UrlParseError> for UrlGenerationError {
     }
 }
 
+/// Errors which can occur when building application router.
+#[derive(Fail, Debug, PartialEq)]
+pub enum RouterError {
+    /// Path pattern can not be parsed
+    #[fail(display="Wrong path pattern: {:?} {}", _0, _1)]
+    Pattern(String, String),
+    /// Both patterns match the same paths or have the same name
+    #[fail(display="Resource {:?} conflicts with {:?}", _1, _0)]
+    Duplicate(String, String),
+    /// Second pattern is never matched, all of its paths match first pattern
+    #[fail(display="Resource {:?} is shadowed by {:?}", _1, _0)]
+    Shadowed(String, String),
+}
+
 macro_rules! ERROR_WRAP {
     ($type:ty, $status:expr) => {
         unsafe impl<T> Sync for $type {}
@@ -600,6 +638,18 @@ mod tests {
         let resp: HttpResponse = ParseError::Incomplete.error_response();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 
+        let resp: HttpResponse = ParseError::TooLarge.error_response();
+        assert_eq!(resp.status(), StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE);
+
+        let resp: HttpResponse = ParseError::UriTooLong.error_response();
+        assert_eq!(resp.status(), StatusCode::URI_TOO_LONG);
+
+        let resp: HttpResponse = ParseError::Timeout.error_response();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+
+        let resp: HttpResponse = PayloadError::Timeout.error_response();
+        assert_eq!(resp.status(), StatusCode::REQUEST_TIMEOUT);
+
         let resp: HttpResponse = HttpRangeError::InvalidRange.error_response();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 
@@ -13,10 +13,11 @@ use futures::{Future, Poll, Async};
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio_core::reactor::Timeout;
 
+use h2;
 use pipeline::Pipeline;
 use encoding::PayloadType;
-use channel::{HttpHandler, HttpHandlerTask};
-use h1writer::{Writer, H1Writer};
+use channel::{HttpHandler, HttpHandlerTask, ConnectionData};
+use h1writer::{Writer, H1Writer, WriteTimeout};
 use worker::WorkerSettings;
 use httpcodes::HTTPNotFound;
 use httprequest::HttpRequest;
@@ -27,14 +28,88 @@ const LW_BUFFER_SIZE: usize = 4096;
 const HW_BUFFER_SIZE: usize = 16_384;
 const MAX_BUFFER_SIZE: usize = 131_072;
 const MAX_HEADERS: usize = 100;
+const MAX_URI_LENGTH: usize = 8_192;
 const MAX_PIPELINED_MESSAGES: usize = 16;
 const HTTP2_PREFACE: [u8; 14] = *b"PRI * HTTP/2.0";
+/// Method, version and separators of request line
+const MAX_REQUEST_LINE_OVERHEAD: usize = 32;
+/// Interval of payload receive rate check, in seconds
+const RATE_CHECK_INTERVAL: u64 = 5;
+/// Maximum size of chunked payload trailers
+const MAX_TRAILERS_SIZE: usize = 8_192;
+
+/// Limits of http/1 request parser
+///
+/// Request with too many headers or too large header section gets
+/// `431 Request Header Fields Too Large` response, request with too long uri
+/// gets `414 URI Too Long` response.
+///
+/// Limits are set with `HttpServer::limits()` method.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::dev::Http1Limits;
+///
+/// fn main() {
+///     let limits = Http1Limits::default()
+///         .max_headers(50)
+///         .max_uri_length(4096);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Http1Limits {
+    max_headers: usize,
+    max_header_size: usize,
+    max_uri_length: usize,
+    max_pipelined: usize,
+}
+
+impl Default for Http1Limits {
+    fn default() -> Http1Limits {
+        Http1Limits {
+            max_headers: MAX_HEADERS,
+            max_header_size: MAX_BUFFER_SIZE,
+            max_uri_length: MAX_URI_LENGTH,
+            max_pipelined: MAX_PIPELINED_MESSAGES,
+        }
+    }
+}
+
+impl Http1Limits {
+    /// Set maximum number of request headers. By default it is 100.
+    pub fn max_headers(mut self, num: usize) -> Self {
+        self.max_headers = num;
+        self
+    }
+
+    /// Set maximum size of request line and headers in bytes.
+    /// By default it is 128Kb.
+    pub fn max_header_size(mut self, size: usize) -> Self {
+        self.max_header_size = size;
+        self
+    }
+
+    /// Set maximum length of request uri. By default it is 8Kb.
+    pub fn max_uri_length(mut self, size: usize) -> Self {
+        self.max_uri_length = size;
+        self
+    }
+
+    /// Set maximum number of pipelined requests processed concurrently.
+    /// Connection stops reading new requests when limit is reached.
+    /// By default it is 16.
+    pub fn max_pipelined(mut self, num: usize) -> Self {
+        self.max_pipelined = num;
+        self
+    }
+}
 
 bitflags! {
     struct Flags: u8 {
         const ERROR = 0b0000_0010;
         const KEEPALIVE = 0b0000_0100;
         const H2 = 0b0000_1000;
+        const LAST_REQUEST = 0b0001_0000;
     }
 }
 
@@ -43,6 +118,8 @@ bitflags! {
         const EOF = 0b0000_0001;
         const ERROR = 0b0000_0010;
         const FINISHED = 0b0000_0100;
+        const CLOSE = 0b0000_1000;
+        const EXPECT = 0b0001_0000;
     }
 }
 
@@ -61,11 +138,18 @@ pub(crate) struct Http1<T: AsyncWrite + 'static, H: 'static> {
     flags: Flags,
     settings: Rc<WorkerSettings<H>>,
     addr: Option<SocketAddr>,
+    data: ConnectionData,
     stream: H1Writer<T>,
     reader: Reader,
     read_buf: BytesMut,
     tasks: VecDeque<Entry>,
     keepalive_timer: Option<Timeout>,
+    client_timer: Option<Timeout>,
+    rate_timer: Option<Timeout>,
+    rate_received: usize,
+    write_timeout: WriteTimeout,
+    requests: usize,
+    upgrade: Option<HttpRequest>,
 }
 
 struct Entry {
@@ -77,24 +161,109 @@ impl<T, H> Http1<T, H>
     where T: AsyncRead + AsyncWrite + 'static,
           H: HttpHandler + 'static
 {
-    pub fn new(h: Rc<WorkerSettings<H>>, stream: T, addr: Option<SocketAddr>) -> Self {
+    pub fn new(h: Rc<WorkerSettings<H>>, stream: T,
+               addr: Option<SocketAddr>, data: ConnectionData) -> Self
+    {
         let bytes = h.get_shared_bytes();
+        // request head timeout starts on connection accept
+        let client_timer = if h.client_timeout() > 0 {
+            Some(Timeout::new(
+                Duration::from_millis(h.client_timeout()), Arbiter::handle()).unwrap())
+        } else {
+            None
+        };
+        let write_timeout = WriteTimeout::new(h.get_write_timeout());
         Http1{ flags: Flags::KEEPALIVE,
                settings: h,
                addr: addr,
+               data: data,
                stream: H1Writer::new(stream, bytes),
                reader: Reader::new(),
                read_buf: BytesMut::new(),
                tasks: VecDeque::new(),
-               keepalive_timer: None }
+               keepalive_timer: None,
+               client_timer: client_timer,
+               rate_timer: None,
+               rate_received: 0,
+               write_timeout: write_timeout,
+               requests: 0,
+               upgrade: None }
     }
 
     pub fn settings(&self) -> &WorkerSettings<H> {
         self.settings.as_ref()
     }
 
-    pub fn into_inner(self) -> (Rc<WorkerSettings<H>>, T, Option<SocketAddr>, Bytes) {
-        (self.settings, self.stream.into_inner(), self.addr, self.read_buf.freeze())
+    /// Returns parts of connection for http/2 handoff, including
+    /// `h2c` upgrade request if any
+    pub fn into_inner(self) -> (Rc<WorkerSettings<H>>, T, Option<SocketAddr>,
+                                ConnectionData, Bytes, Option<HttpRequest>)
+    {
+        (self.settings, self.stream.into_inner(), self.addr,
+         self.data, self.read_buf.freeze(), self.upgrade)
+    }
+
+    fn start_client_timer(&mut self) {
+        let timeout = self.settings.client_timeout();
+        if timeout > 0 && self.client_timer.is_none() {
+            trace!("Start slow request timer");
+            let mut to = Timeout::new(
+                Duration::from_millis(timeout), Arbiter::handle()).unwrap();
+            // register timeout
+            let _ = to.poll();
+            self.client_timer = Some(to);
+        }
+    }
+
+    fn start_rate_timer(&mut self) {
+        if self.settings.min_body_rate() > 0 && self.reader.payload.is_some() {
+            let mut to = Timeout::new(
+                Duration::new(RATE_CHECK_INTERVAL, 0), Arbiter::handle()).unwrap();
+            // register timeout
+            let _ = to.poll();
+            self.rate_timer = Some(to);
+            self.rate_received = self.reader.received;
+        }
+    }
+
+    /// Check slow request timers, returns true if request is timed out
+    fn poll_client_timers(&mut self) -> bool {
+        if let Some(ref mut timer) = self.client_timer {
+            match timer.poll() {
+                Ok(Async::Ready(_)) => {
+                    trace!("Slow request timeout, close connection");
+                    self.tasks.push_back(
+                        Entry {pipe: Pipeline::error(ParseError::Timeout.error_response()),
+                               flags: EntryFlags::empty()});
+                    return true
+                }
+                Ok(Async::NotReady) => (),
+                Err(_) => unreachable!(),
+            }
+        }
+
+        let expired = match self.rate_timer {
+            Some(ref mut timer) => match timer.poll() {
+                Ok(Async::Ready(_)) => true,
+                Ok(Async::NotReady) => false,
+                Err(_) => unreachable!(),
+            },
+            None => false,
+        };
+        if expired {
+            self.rate_timer.take();
+            let received = self.reader.received - self.rate_received;
+            let min = self.settings.min_body_rate() * RATE_CHECK_INTERVAL as usize;
+
+            // paused payload is not client's fault
+            if self.reader.payload.is_some() && !self.reader.is_paused() && received < min {
+                trace!("Slow payload timeout, close connection");
+                self.reader.set_error(PayloadError::Timeout);
+                return true
+            }
+            self.start_rate_timer();
+        }
+        false
     }
 
     fn poll_completed(&mut self) -> Result<bool, ()> {
@@ -109,9 +278,27 @@ impl<T, H> Http1<T, H>
         }
     }
 
+    pub fn poll(&mut self) -> Poll<Http1Result, ()> {
+        let res = self.poll_io();
+
+        // client does not read response
+        if let Ok(Async::NotReady) = res {
+            let progress = self.stream.take_progress();
+            if self.write_timeout.expired(self.stream.is_pending(), progress) {
+                info!("Write timeout, abort connection from {:?}", self.addr);
+                self.stream.disconnected();
+                for entry in &mut self.tasks {
+                    entry.pipe.disconnected()
+                }
+                return Err(())
+            }
+        }
+        res
+    }
+
     // TODO: refacrtor
     #[cfg_attr(feature = "cargo-clippy", allow(cyclomatic_complexity))]
-    pub fn poll(&mut self) -> Poll<Http1Result, ()> {
+    fn poll_io(&mut self) -> Poll<Http1Result, ()> {
         // keep-alive timer
         if self.keepalive_timer.is_some() {
             match self.keepalive_timer.as_mut().unwrap().poll() {
@@ -124,6 +311,30 @@ impl<T, H> Http1<T, H>
             }
         }
 
+        // server shutdown, close connection after current request
+        if self.settings.is_shutdown() && !self.flags.contains(Flags::LAST_REQUEST) {
+            self.flags.insert(Flags::LAST_REQUEST);
+
+            // started keep-alive response is finished as is,
+            // connection gets closed after it
+            let len = self.tasks.len();
+            let pos = self.tasks.iter().position(|e| !e.flags.contains(EntryFlags::EOF));
+            if let Some(idx) = pos {
+                if idx + 1 < len || !self.stream.is_started() {
+                    self.tasks[len - 1].flags.insert(EntryFlags::CLOSE);
+                }
+            }
+        }
+
+        // slow request, stop reading and close connection after response
+        if self.poll_client_timers() {
+            self.client_timer.take();
+            self.rate_timer.take();
+            self.keepalive_timer.take();
+            self.flags.remove(Flags::KEEPALIVE);
+            self.flags.insert(Flags::ERROR);
+        }
+
         loop {
             let mut not_ready = true;
 
@@ -142,6 +353,16 @@ impl<T, H> Http1<T, H>
                         return Err(())
                     }
 
+                    // last request on this connection
+                    if item.flags.contains(EntryFlags::CLOSE) {
+                        self.stream.force_close();
+                    }
+                    // client waits for `100 Continue`
+                    if item.flags.contains(EntryFlags::EXPECT) {
+                        item.flags.remove(EntryFlags::EXPECT);
+                        self.stream.expect_continue();
+                    }
+
                     match item.pipe.poll_io(&mut self.stream) {
                         Ok(Async::Ready(ready)) => {
                             not_ready = false;
@@ -205,8 +426,15 @@ impl<T, H> Http1<T, H>
                 }
             }
 
-            // no keep-alive
-            if !self.flags.contains(Flags::KEEPALIVE) && self.tasks.is_empty() {
+            // handler started reading request payload
+            if self.reader.need_read() {
+                self.stream.write_continue();
+            }
+
+            // no keep-alive or requests limit reached
+            if (!self.flags.contains(Flags::KEEPALIVE) ||
+                self.flags.contains(Flags::LAST_REQUEST)) && self.tasks.is_empty()
+            {
                 // check stream state
                 if self.poll_completed()? {
                     return Ok(Async::NotReady)
@@ -219,19 +447,53 @@ impl<T, H> Http1<T, H>
             }
 
             // read incoming data
+            // after last request, only its payload gets read
             while !self.flags.contains(Flags::ERROR) && !self.flags.contains(Flags::H2) &&
-                self.tasks.len() < MAX_PIPELINED_MESSAGES
+                self.tasks.len() < self.settings.limits().max_pipelined &&
+                (!self.flags.contains(Flags::LAST_REQUEST) || self.reader.payload.is_some())
             {
                 match self.reader.parse(self.stream.get_mut(),
                                         &mut self.read_buf, &self.settings) {
                     Ok(Async::Ready(Item::Http1(mut req))) => {
                         not_ready = false;
 
-                        // set remote addr
+                        // set remote addr and connection data
                         req.set_peer_addr(self.addr);
+                        self.data.apply(req.extensions());
 
-                        // stop keepalive timer
+                        // stop keepalive and request head timers
                         self.keepalive_timer.take();
+                        self.client_timer.take();
+
+                        // http/2 cleartext upgrade, request is served as http/2 stream,
+                        // it is not allowed over tls
+                        if !self.settings.is_secure() &&
+                            self.tasks.is_empty() && self.reader.payload.is_none() &&
+                            !self.flags.contains(Flags::LAST_REQUEST) &&
+                            h2c_upgrade(req.version(), req.headers())
+                        {
+                            self.stream.upgrade_h2c();
+                            self.upgrade = Some(req);
+                            self.flags.insert(Flags::H2);
+                            break
+                        }
+                        self.start_rate_timer();
+
+                        // payload is sent after `100 Continue`
+                        let mut flags = EntryFlags::empty();
+                        match req.expect_continue() {
+                            Ok(true) if self.reader.payload.is_some() =>
+                                flags.insert(EntryFlags::EXPECT),
+                            Ok(_) => (),
+                            Err(err) => {
+                                // unsupported expectation, payload never gets read
+                                self.flags.insert(Flags::ERROR);
+                                self.tasks.push_back(
+                                    Entry {pipe: Pipeline::error(err.error_response()),
+                                           flags: EntryFlags::CLOSE});
+                                continue
+                            }
+                        }
 
                         // start request processing
                         let mut pipe = None;
@@ -245,9 +507,17 @@ impl<T, H> Http1<T, H>
                             }
                         }
 
+                        // requests limit
+                        self.requests += 1;
+                        let max = self.settings.get_max_requests();
+                        if max > 0 && self.requests >= max {
+                            self.flags.insert(Flags::LAST_REQUEST);
+                            flags.insert(EntryFlags::CLOSE);
+                        }
+
                         self.tasks.push_back(
                             Entry {pipe: pipe.unwrap_or_else(|| Pipeline::error(HTTPNotFound)),
-                                   flags: EntryFlags::empty()});
+                                   flags: flags});
                     }
                     Ok(Async::Ready(Item::Http2)) => {
                         self.flags.insert(Flags::H2);
@@ -284,6 +554,11 @@ impl<T, H> Http1<T, H>
                         }
                     }
                     Ok(Async::NotReady) => {
+                        // first bytes of next request head
+                        if !self.read_buf.is_empty() && self.reader.payload.is_none() {
+                            self.start_client_timer();
+                        }
+
                         // start keep-alive timer, this is also slow request timeout
                         if self.tasks.is_empty() {
                             if self.settings.keep_alive_enabled() {
@@ -344,6 +619,7 @@ impl<T, H> Http1<T, H>
 struct Reader {
     h1: bool,
     payload: Option<PayloadInfo>,
+    received: usize,
 }
 
 enum Decoding {
@@ -375,6 +651,30 @@ impl Reader {
         Reader {
             h1: false,
             payload: None,
+            received: 0,
+        }
+    }
+
+    /// Payload buffer is full, application does not read payload
+    fn is_paused(&self) -> bool {
+        if let Some(ref payload) = self.payload {
+            payload.tx.capacity() == 0
+        } else {
+            false
+        }
+    }
+
+    fn need_read(&self) -> bool {
+        if let Some(ref payload) = self.payload {
+            payload.tx.need_read()
+        } else {
+            false
+        }
+    }
+
+    fn set_error(&mut self, err: PayloadError) {
+        if let Some(mut payload) = self.payload.take() {
+            payload.tx.set_error(err);
         }
     }
 
@@ -387,9 +687,13 @@ impl Reader {
             loop {
                 match payload.decoder.decode(buf) {
                     Ok(Async::Ready(Some(bytes))) => {
+                        self.received += bytes.len();
                         payload.tx.feed_data(bytes)
                     },
                     Ok(Async::Ready(None)) => {
+                        if let Some(trailers) = payload.decoder.trailers.take() {
+                            payload.tx.set_trailers(trailers);
+                        }
                         payload.tx.feed_eof();
                         return Ok(Decoding::Ready)
                     },
@@ -474,10 +778,18 @@ impl Reader {
                     return Ok(Async::Ready(Item::Http2));
                 },
                 Message::NotReady => {
-                    if buf.capacity() >= MAX_BUFFER_SIZE {
-                        error!("MAX_BUFFER_SIZE unprocessed data reached, closing");
+                    let limits = settings.limits();
+                    if buf.len() >= limits.max_header_size {
+                        debug!("Request header size limit is reached, closing");
                         return Err(ReaderError::Error(ParseError::TooLarge));
                     }
+                    // request line is not complete yet
+                    if buf.len() > limits.max_uri_length + MAX_REQUEST_LINE_OVERHEAD &&
+                        !buf.contains(&b'\n')
+                    {
+                        debug!("Request uri length limit is reached, closing");
+                        return Err(ReaderError::Error(ParseError::UriTooLong));
+                    }
                     if read {
                         match self.read_from_io(io, buf) {
                             Ok(Async::Ready(0)) => {
@@ -531,20 +843,35 @@ impl Reader {
             return Ok(Message::Http2)
         }
 
+        let limits = settings.limits();
+
         // Parse http message
         let msg = {
             let bytes_ptr = buf.as_ref().as_ptr() as usize;
             let mut headers: [httparse::Header; MAX_HEADERS] =
                 unsafe{std::mem::uninitialized()};
+            let mut large_headers;
+            let headers: &mut [httparse::Header] = if limits.max_headers <= MAX_HEADERS {
+                &mut headers[..limits.max_headers]
+            } else {
+                large_headers = vec![httparse::EMPTY_HEADER; limits.max_headers];
+                &mut large_headers
+            };
 
             let (len, method, path, version, headers_len) = {
                 let b = unsafe{ let b: &[u8] = buf; std::mem::transmute(b) };
-                let mut req = httparse::Request::new(&mut headers);
+                let mut req = httparse::Request::new(headers);
                 match req.parse(b)? {
                     httparse::Status::Complete(len) => {
+                        if len > limits.max_header_size {
+                            return Err(ParseError::TooLarge)
+                        }
                         let method = Method::try_from(req.method.unwrap())
                             .map_err(|_| ParseError::Method)?;
                         let path = req.path.unwrap();
+                        if path.len() > limits.max_uri_length {
+                            return Err(ParseError::UriTooLong)
+                        }
                         let path_start = path.as_ptr() as usize - bytes_ptr;
                         let path_end = path_start + path.len();
                         let path = (path_start, path_end);
@@ -601,7 +928,8 @@ impl Reader {
         } else if chunked(&msg.get_mut().headers)? {
             // Chunked encoding
             Some(Decoder::chunked())
-        } else if msg.get_ref().headers.contains_key(header::UPGRADE) ||
+        } else if (msg.get_ref().headers.contains_key(header::UPGRADE) &&
+                   !h2c_upgrade(msg.get_ref().version, &msg.get_ref().headers)) ||
             msg.get_ref().method == Method::CONNECT
         {
             Some(Decoder::eof())
@@ -623,6 +951,24 @@ impl Reader {
     }
 }
 
+/// Check if request asks for http/2 cleartext upgrade, RFC 7540 section 3.2
+fn h2c_upgrade(version: Version, headers: &HeaderMap) -> bool {
+    if version != Version::HTTP_11 || headers.get_all("http2-settings").iter().count() != 1 {
+        return false
+    }
+    let upgrade = match headers.get(header::UPGRADE).and_then(|v| v.to_str().ok()) {
+        Some(upgrade) => upgrade.to_lowercase(),
+        None => return false,
+    };
+    let conn = match headers.get(header::CONNECTION).and_then(|v| v.to_str().ok()) {
+        Some(conn) => conn.to_lowercase(),
+        None => return false,
+    };
+    upgrade.split(',').any(|proto| proto.trim() == "h2c") &&
+        conn.contains("upgrade") && conn.contains("http2-settings") &&
+        headers.get("http2-settings").and_then(|v| h2::decode_settings(v.as_bytes())).is_some()
+}
+
 /// Check if request has chunked transfer encoding
 fn chunked(headers: &HeaderMap) -> Result<bool, ParseError> {
     if let Some(encodings) = headers.get(header::TRANSFER_ENCODING) {
@@ -643,19 +989,20 @@ fn chunked(headers: &HeaderMap) -> Result<bool, ParseError> {
 #[derive(Debug, Clone, PartialEq)]
 struct Decoder {
     kind: Kind,
+    trailers: Option<HeaderMap>,
 }
 
 impl Decoder {
     pub fn length(x: u64) -> Decoder {
-        Decoder { kind: Kind::Length(x) }
+        Decoder { kind: Kind::Length(x), trailers: None }
     }
 
     pub fn chunked() -> Decoder {
-        Decoder { kind: Kind::Chunked(ChunkedState::Size, 0) }
+        Decoder { kind: Kind::Chunked(ChunkedState::Size, 0), trailers: None }
     }
 
     pub fn eof() -> Decoder {
-        Decoder { kind: Kind::Eof(false) }
+        Decoder { kind: Kind::Eof(false), trailers: None }
     }
 }
 
@@ -691,8 +1038,7 @@ enum ChunkedState {
     Body,
     BodyCr,
     BodyLf,
-    EndCr,
-    EndLf,
+    Trailers,
     End,
 }
 
@@ -721,6 +1067,14 @@ impl Decoder {
             }
             Kind::Chunked(ref mut state, ref mut size) => {
                 loop {
+                    // trailer section after last chunk
+                    if *state == ChunkedState::Trailers {
+                        self.trailers = try_ready!(read_trailers(body));
+                        *state = ChunkedState::End;
+                        trace!("End of chunked stream");
+                        return Ok(Async::Ready(None));
+                    }
+
                     let mut buf = None;
                     // advances the chunked state
                     *state = try_ready!(state.step(body, size, &mut buf));
@@ -774,9 +1128,7 @@ impl ChunkedState {
             Body => ChunkedState::read_body(body, size, buf),
             BodyCr => ChunkedState::read_body_cr(body),
             BodyLf => ChunkedState::read_body_lf(body),
-            EndCr => ChunkedState::read_end_cr(body),
-            EndLf => ChunkedState::read_end_lf(body),
-            End => Ok(Async::Ready(ChunkedState::End)),
+            Trailers | End => Ok(Async::Ready(self.clone())),
         }
     }
     fn read_size(rdr: &mut BytesMut, size: &mut u64) -> Poll<ChunkedState, io::Error> {
@@ -826,7 +1178,7 @@ impl ChunkedState {
     fn read_size_lf(rdr: &mut BytesMut, size: &mut u64) -> Poll<ChunkedState, io::Error> {
         match byte!(rdr) {
             b'\n' if *size > 0 => Ok(Async::Ready(ChunkedState::Body)),
-            b'\n' if *size == 0 => Ok(Async::Ready(ChunkedState::EndCr)),
+            b'\n' if *size == 0 => Ok(Async::Ready(ChunkedState::Trailers)),
             _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid chunk size LF")),
         }
     }
@@ -869,18 +1221,42 @@ impl ChunkedState {
             _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid chunk body LF")),
         }
     }
-    fn read_end_cr(rdr: &mut BytesMut) -> Poll<ChunkedState, io::Error> {
-        match byte!(rdr) {
-            b'\r' => Ok(Async::Ready(ChunkedState::EndLf)),
-            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid chunk end CR")),
-        }
-    }
-    fn read_end_lf(rdr: &mut BytesMut) -> Poll<ChunkedState, io::Error> {
-        match byte!(rdr) {
-            b'\n' => Ok(Async::Ready(ChunkedState::End)),
-            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid chunk end LF")),
+}
+
+/// Read trailer section of chunked payload, ends with empty line
+fn read_trailers(rdr: &mut BytesMut) -> Poll<Option<HeaderMap>, io::Error> {
+    if rdr.starts_with(b"\r\n") {
+        rdr.split_to(2);
+        return Ok(Async::Ready(None))
+    }
+    let pos = match rdr.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(pos) => pos,
+        None if rdr.len() <= MAX_TRAILERS_SIZE => return Ok(Async::NotReady),
+        None => rdr.len(),
+    };
+    if pos > MAX_TRAILERS_SIZE {
+        return Err(
+            io::Error::new(io::ErrorKind::InvalidInput, "Chunked trailers are too large"))
+    }
+    let block = rdr.split_to(pos + 4);
+
+    let mut trailers = HeaderMap::new();
+    for line in block[..pos].split(|c| *c == b'\n') {
+        let line = if line.ends_with(b"\r") { &line[..line.len() - 1] } else { line };
+        let idx = match line.iter().position(|c| *c == b':') {
+            Some(idx) => idx,
+            None => return Err(
+                io::Error::new(io::ErrorKind::InvalidInput, "Invalid chunked trailer")),
+        };
+        let value = &line[idx + 1..];
+        let start = value.iter().position(|c| *c != b' ' && *c != b'\t').unwrap_or(value.len());
+        match (HeaderName::from_bytes(&line[..idx]), HeaderValue::from_bytes(&value[start..])) {
+            (Ok(name), Ok(value)) => { trailers.append(name, value); },
+            _ => return Err(
+                io::Error::new(io::ErrorKind::InvalidInput, "Invalid chunked trailer")),
         }
     }
+    Ok(Async::Ready(Some(trailers)))
 }
 
 #[cfg(test)]
@@ -1227,6 +1603,38 @@ mod tests {
         assert!(req.upgrade());
     }
 
+    #[test]
+    fn test_conn_upgrade_h2c() {
+        let mut buf = Buffer::new(
+            "GET /test HTTP/1.1\r\n\
+             upgrade: h2c\r\n\
+             connection: Upgrade, HTTP2-Settings\r\n\
+             http2-settings: AAMAAABkAAQAAP__\r\n\r\n");
+        let req = parse_ready!(&mut buf);
+
+        assert!(req.payload().eof());
+        assert!(h2c_upgrade(req.version(), req.headers()));
+
+        let mut buf = Buffer::new(
+            "GET /test HTTP/1.1\r\n\
+             upgrade: h2c\r\n\
+             connection: upgrade\r\n\r\n");
+        let req = parse_ready!(&mut buf);
+
+        assert!(!req.payload().eof());
+        assert!(!h2c_upgrade(req.version(), req.headers()));
+
+        // settings are not valid base64url
+        let mut buf = Buffer::new(
+            "GET /test HTTP/1.1\r\n\
+             upgrade: h2c\r\n\
+             connection: Upgrade, HTTP2-Settings\r\n\
+             http2-settings: AAMAAABkAAQAAP//\r\n\r\n");
+        let req = parse_ready!(&mut buf);
+
+        assert!(!h2c_upgrade(req.version(), req.headers()));
+    }
+
     #[test]
     fn test_conn_upgrade_connect_method() {
         let mut buf = Buffer::new(
@@ -1299,6 +1707,65 @@ mod tests {
         expect_parse_err!(&mut buf);
     }
 
+    fn parse_limits(data: String, limits: Http1Limits) -> Option<ParseError> {
+        let mut buf = Buffer{buf: Bytes::from(data), err: None};
+        let settings = WorkerSettings::<HttpApplication>::new(Vec::new(), None)
+            .h1_limits(limits);
+        match Reader::new().parse(&mut buf, &mut BytesMut::new(), &settings) {
+            Err(ReaderError::Error(err)) => Some(err),
+            Err(_) => panic!("Parse error expected"),
+            Ok(_) => None,
+        }
+    }
+
+    #[test]
+    fn test_limits_max_headers() {
+        let mut data = "GET /test HTTP/1.1\r\n".to_owned();
+        for idx in 0..10 {
+            data.push_str(&format!("x-header-{}: value\r\n", idx));
+        }
+        data.push_str("\r\n");
+
+        assert!(parse_limits(data.clone(), Http1Limits::default()).is_none());
+        match parse_limits(data, Http1Limits::default().max_headers(5)) {
+            Some(ParseError::TooLarge) => (),
+            _ => panic!("TooLarge error expected"),
+        }
+    }
+
+    #[test]
+    fn test_limits_large_headers() {
+        let mut data = "GET /test HTTP/1.1\r\n".to_owned();
+        for idx in 0..200 {
+            data.push_str(&format!("x-header-{}: value\r\n", idx));
+        }
+        data.push_str("\r\n");
+
+        match parse_limits(data, Http1Limits::default().max_headers(300).max_header_size(1024)) {
+            Some(ParseError::TooLarge) => (),
+            _ => panic!("TooLarge error expected"),
+        }
+    }
+
+    #[test]
+    fn test_limits_max_uri_length() {
+        let uri: String = ::std::iter::repeat("a").take(100).collect();
+        let data = format!("GET /{} HTTP/1.1\r\n\r\n", uri);
+
+        assert!(parse_limits(data.clone(), Http1Limits::default()).is_none());
+        match parse_limits(data, Http1Limits::default().max_uri_length(50)) {
+            Some(ParseError::UriTooLong) => (),
+            _ => panic!("UriTooLong error expected"),
+        }
+
+        // incomplete request line
+        let data = format!("GET /{}", uri);
+        match parse_limits(data, Http1Limits::default().max_uri_length(50)) {
+            Some(ParseError::UriTooLong) => (),
+            _ => panic!("UriTooLong error expected"),
+        }
+    }
+
     #[test]
     fn test_http_request_bad_status_line() {
         let mut buf = Buffer::new("getpath \r\n\r\n");
@@ -1442,6 +1909,46 @@ mod tests {
         assert!(req.payload().eof());
     }
 
+    #[test]
+    fn test_http_request_chunked_payload_trailers() {
+        let mut buf = Buffer::new(
+            "GET /test HTTP/1.1\r\n\
+             transfer-encoding: chunked\r\n\r\n");
+        let mut readbuf = BytesMut::new();
+        let settings = WorkerSettings::<HttpApplication>::new(Vec::new(), None);
+
+        let mut reader = Reader::new();
+        let mut req = reader_parse_ready!(reader.parse(&mut buf, &mut readbuf, &settings));
+
+        buf.feed_data("4\r\ndata\r\n0\r\nchecksum: abc\r\n");
+        not_ready!(reader.parse(&mut buf, &mut readbuf, &settings));
+        assert!(req.trailers().is_none());
+
+        buf.feed_data("grpc-status:  0\r\n\r\n");
+        not_ready!(reader.parse(&mut buf, &mut readbuf, &settings));
+        assert_eq!(req.payload_mut().readall().unwrap().as_ref(), b"data");
+        assert!(req.payload().eof());
+
+        let trailers = req.trailers().unwrap();
+        assert_eq!(trailers.get("checksum").unwrap(), "abc");
+        assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+    }
+
+    #[test]
+    fn test_read_trailers_too_large() {
+        let mut buf = BytesMut::from(format!("x: {}\r\n\r\n", "a".repeat(MAX_TRAILERS_SIZE)));
+        assert!(read_trailers(&mut buf).is_err());
+
+        let mut buf = BytesMut::from(format!("x: {}", "a".repeat(MAX_TRAILERS_SIZE)));
+        assert!(read_trailers(&mut buf).is_err());
+
+        let mut buf = BytesMut::from("x: a\r\n");
+        match read_trailers(&mut buf) {
+            Ok(Async::NotReady) => (),
+            _ => panic!("trailers are not complete"),
+        }
+    }
+
     #[test]
     fn test_parse_chunked_payload_chunk_extension() {
         let mut buf = Buffer::new(
@@ -1492,3 +1999,21 @@ mod tests {
         }
     }
 }
+
+    #[test]
+    fn test_reader_paused() {
+        let (tx, _payload) = Payload::new(false);
+        let mut reader = Reader::new();
+        reader.payload = Some(PayloadInfo {
+            tx: PayloadType::new(&HeaderMap::new(), tx),
+            decoder: Decoder::length(DEFAULT_BUFFER_SIZE as u64 * 2),
+        });
+        assert!(!reader.is_paused());
+
+        // application does not read payload, buffer is full
+        if let Some(ref mut payload) = reader.payload {
+            payload.tx.feed_data(Bytes::from(vec![0; DEFAULT_BUFFER_SIZE]));
+        }
+        assert!(reader.is_paused());
+    }
+}
@@ -1,7 +1,10 @@
 use std::io;
+use std::time::Duration;
+use actix::Arbiter;
 use bytes::BufMut;
-use futures::{Async, Poll};
+use futures::{Async, Future, Poll};
 use tokio_io::AsyncWrite;
+use tokio_core::reactor::Timeout;
 use http::Version;
 use http::header::{HeaderValue, CONNECTION, DATE};
 
@@ -22,6 +25,43 @@ pub enum WriterState {
     Pause,
 }
 
+/// Slow client detection.
+///
+/// Timer runs while writer has unflushed data, it restarts every time
+/// some data is flushed.
+pub(crate) struct WriteTimeout {
+    timeout: u64,
+    timer: Option<Timeout>,
+}
+
+impl WriteTimeout {
+    pub fn new(timeout: u64) -> WriteTimeout {
+        WriteTimeout { timeout: timeout, timer: None }
+    }
+
+    /// Check write progress, returns `true` if no data
+    /// has been flushed within timeout.
+    pub fn expired(&mut self, pending: bool, progress: bool) -> bool {
+        if self.timeout == 0 || !pending {
+            self.timer.take();
+            return false
+        }
+        if progress || self.timer.is_none() {
+            let mut timer = Timeout::new(
+                Duration::new(self.timeout, 0), Arbiter::handle()).unwrap();
+            // register timeout
+            let _ = timer.poll();
+            self.timer = Some(timer);
+            return false
+        }
+        match self.timer.as_mut().unwrap().poll() {
+            Ok(Async::Ready(_)) => true,
+            Ok(Async::NotReady) => false,
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
 /// Send stream
 pub trait Writer {
     fn written(&self) -> u64;
@@ -42,6 +82,8 @@ bitflags! {
         const UPGRADE = 0b0000_0010;
         const KEEPALIVE = 0b0000_0100;
         const DISCONNECTED = 0b0000_1000;
+        const FORCE_CLOSE = 0b0001_0000;
+        const EXPECT = 0b0010_0000;
     }
 }
 
@@ -52,6 +94,7 @@ pub(crate) struct H1Writer<T: AsyncWrite> {
     written: u64,
     headers_size: u32,
     buffer: SharedBytes,
+    progress: bool,
 }
 
 impl<T: AsyncWrite> H1Writer<T> {
@@ -64,6 +107,7 @@ impl<T: AsyncWrite> H1Writer<T> {
             written: 0,
             headers_size: 0,
             buffer: buf,
+            progress: false,
         }
     }
 
@@ -84,6 +128,48 @@ impl<T: AsyncWrite> H1Writer<T> {
         self.encoder.get_mut().take();
     }
 
+    /// Accept http/2 cleartext upgrade
+    pub fn upgrade_h2c(&mut self) {
+        self.encoder.get_mut().extend_from_slice(
+            b"HTTP/1.1 101 Switching Protocols\r\nconnection: upgrade\r\nupgrade: h2c\r\n\r\n");
+    }
+
+    /// Client waits for `100 Continue` before sending request payload
+    pub fn expect_continue(&mut self) {
+        self.flags.insert(Flags::EXPECT);
+    }
+
+    /// Allow client to send request payload. Nothing get written
+    /// if response is started already.
+    pub fn write_continue(&mut self) {
+        if self.flags.contains(Flags::EXPECT) && !self.flags.contains(Flags::STARTED) {
+            self.flags.remove(Flags::EXPECT);
+            self.encoder.get_mut().extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n");
+        }
+    }
+
+    /// Close connection after current response
+    pub fn force_close(&mut self) {
+        self.flags.insert(Flags::FORCE_CLOSE);
+    }
+
+    /// Response head of current response is written
+    pub fn is_started(&self) -> bool {
+        self.flags.contains(Flags::STARTED)
+    }
+
+    /// Writer has data which is not flushed to the stream
+    pub fn is_pending(&self) -> bool {
+        self.encoder.len() > 0
+    }
+
+    /// Some data has been flushed since last call
+    pub fn take_progress(&mut self) -> bool {
+        let progress = self.progress;
+        self.progress = false;
+        progress
+    }
+
     pub fn keepalive(&self) -> bool {
         self.flags.contains(Flags::KEEPALIVE) && !self.flags.contains(Flags::UPGRADE)
     }
@@ -96,6 +182,7 @@ impl<T: AsyncWrite> H1Writer<T> {
                 Ok(n) => {
                     buffer.split_to(n);
                     self.written += n as u64;
+                    self.progress = true;
                 },
                 Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
                     if buffer.len() > MAX_WRITE_BUFFER_SIZE {
@@ -128,7 +215,10 @@ impl<T: AsyncWrite> Writer for H1Writer<T> {
         // prepare task
         self.flags.insert(Flags::STARTED);
         self.encoder = PayloadEncoder::new(self.buffer.clone(), req, msg);
-        if msg.keep_alive().unwrap_or_else(|| req.keep_alive()) {
+        // request payload has not been sent, connection state is unknown
+        if msg.keep_alive().unwrap_or_else(|| req.keep_alive()) &&
+            !self.flags.contains(Flags::FORCE_CLOSE) && !self.flags.contains(Flags::EXPECT)
+        {
             self.flags.insert(Flags::KEEPALIVE);
         }
 
@@ -145,6 +235,7 @@ impl<T: AsyncWrite> Writer for H1Writer<T> {
         } else if version >= Version::HTTP_11 {
             msg.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
         }
+
         let body = msg.replace_body(Body::Empty);
 
         // render message
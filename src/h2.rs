@@ -6,9 +6,10 @@ use std::net::SocketAddr;
 use std::collections::VecDeque;
 
 use actix::Arbiter;
+use http::header;
 use http::request::Parts;
 use http2::{Reason, RecvStream};
-use http2::server::{Server, Handshake, Respond};
+use http2::server::{self, Server, Handshake, Respond};
 use bytes::{Buf, Bytes};
 use futures::{Async, Poll, Future, Stream};
 use tokio_io::{AsyncRead, AsyncWrite};
@@ -16,17 +17,124 @@ use tokio_core::reactor::Timeout;
 
 use pipeline::Pipeline;
 use h2writer::H2Writer;
+use h1writer::WriteTimeout;
 use worker::WorkerSettings;
-use channel::{HttpHandler, HttpHandlerTask};
+use channel::{HttpHandler, HttpHandlerTask, ConnectionData};
 use error::PayloadError;
 use encoding::PayloadType;
 use httpcodes::HTTPNotFound;
 use httprequest::HttpRequest;
 use payload::{Payload, PayloadWriter};
 
+/// Client connection preface
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const FRAME_HEADER_SIZE: usize = 9;
+/// Frame size every http/2 endpoint has to accept
+const DEFAULT_MAX_FRAME_SIZE: usize = 16_384;
+const MAX_MAX_FRAME_SIZE: u32 = 16_777_215;
+const MAX_WINDOW_SIZE: u32 = 2_147_483_647;
+const FRAME_HEADERS: u8 = 0x1;
+const FRAME_SETTINGS: u8 = 0x4;
+const FRAME_CONTINUATION: u8 = 0x9;
+const FLAG_END_STREAM: u8 = 0x1;
+const FLAG_END_HEADERS: u8 = 0x4;
+
 bitflags! {
     struct Flags: u8 {
         const DISCONNECTED = 0b0000_0010;
+        const GOAWAY = 0b0000_0100;
+    }
+}
+
+/// Http/2 connection settings
+///
+/// Unset values use defaults of http/2 implementation.
+/// Settings are set with `HttpServer::h2_settings()` method.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::dev::Http2Settings;
+///
+/// fn main() {
+///     let settings = Http2Settings::default()
+///         .max_concurrent_streams(100)
+///         .initial_window_size(1_048_576);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Http2Settings {
+    max_concurrent_streams: Option<u32>,
+    initial_window_size: Option<u32>,
+    initial_connection_window_size: Option<u32>,
+    max_frame_size: Option<u32>,
+    max_header_list_size: Option<u32>,
+}
+
+impl Http2Settings {
+    /// Set maximum number of concurrent streams a client can open.
+    pub fn max_concurrent_streams(mut self, num: u32) -> Self {
+        self.max_concurrent_streams = Some(num);
+        self
+    }
+
+    /// Set initial flow control window size of a stream in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if size is bigger than 2^31-1.
+    pub fn initial_window_size(mut self, size: u32) -> Self {
+        assert!(size <= MAX_WINDOW_SIZE, "Window size can not exceed 2^31-1: {}", size);
+        self.initial_window_size = Some(size);
+        self
+    }
+
+    /// Set initial flow control window size of a connection in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if size is bigger than 2^31-1.
+    pub fn initial_connection_window_size(mut self, size: u32) -> Self {
+        assert!(size <= MAX_WINDOW_SIZE, "Window size can not exceed 2^31-1: {}", size);
+        self.initial_connection_window_size = Some(size);
+        self
+    }
+
+    /// Set maximum frame payload size in bytes, between 16Kb and 16Mb.
+    ///
+    /// # Panics
+    ///
+    /// Panics if size is not in 16384..=16777215 range.
+    pub fn max_frame_size(mut self, size: u32) -> Self {
+        assert!(size as usize >= DEFAULT_MAX_FRAME_SIZE && size <= MAX_MAX_FRAME_SIZE,
+                "Frame size has to be between 16384 and 16777215: {}", size);
+        self.max_frame_size = Some(size);
+        self
+    }
+
+    /// Set maximum size of request header list in bytes.
+    pub fn max_header_list_size(mut self, size: u32) -> Self {
+        self.max_header_list_size = Some(size);
+        self
+    }
+
+    fn handshake<T: AsyncRead + AsyncWrite>(&self, io: T) -> Handshake<T, Bytes> {
+        let mut builder = server::Builder::default();
+        if let Some(num) = self.max_concurrent_streams {
+            builder.max_concurrent_streams(num);
+        }
+        if let Some(size) = self.initial_window_size {
+            builder.initial_window_size(size);
+        }
+        if let Some(size) = self.initial_connection_window_size {
+            builder.initial_connection_window_size(size);
+        }
+        if let Some(size) = self.max_frame_size {
+            builder.max_frame_size(size);
+        }
+        if let Some(size) = self.max_header_list_size {
+            builder.max_header_list_size(size);
+        }
+        builder.handshake(io)
     }
 }
 
@@ -37,12 +145,17 @@ pub(crate) struct Http2<T, H>
     flags: Flags,
     settings: Rc<WorkerSettings<H>>,
     addr: Option<SocketAddr>,
+    data: ConnectionData,
     state: State<IoWrapper<T>>,
     tasks: VecDeque<Entry>,
     keepalive_timer: Option<Timeout>,
+    requests: usize,
 }
 
 enum State<T: AsyncRead + AsyncWrite> {
+    /// `h2c` upgrade, client preface, decoded `HTTP2-Settings`
+    /// and encoded upgrade request
+    Upgrade(T, Vec<u8>, Vec<u8>, Vec<u8>),
     Handshake(Handshake<T, Bytes>),
     Server(Server<T, Bytes>),
     Empty,
@@ -52,15 +165,27 @@ impl<T, H> Http2<T, H>
     where T: AsyncRead + AsyncWrite + 'static,
           H: HttpHandler + 'static
 {
-    pub fn new(h: Rc<WorkerSettings<H>>, io: T, addr: Option<SocketAddr>, buf: Bytes) -> Self
+    pub fn new(h: Rc<WorkerSettings<H>>, io: T, addr: Option<SocketAddr>,
+               data: ConnectionData, buf: Bytes, upgrade: Option<HttpRequest>) -> Self
     {
+        let io = IoWrapper{unread: Some(buf), inner: io};
+        let state = if let Some(req) = upgrade {
+            // header is validated before upgrade
+            let settings = req.headers().get("http2-settings")
+                .and_then(|val| decode_settings(val.as_bytes()))
+                .unwrap_or_default();
+            State::Upgrade(io, Vec::new(), settings, upgrade_frames(&req))
+        } else {
+            State::Handshake(h.h2_settings().handshake(io))
+        };
         Http2{ flags: Flags::empty(),
                settings: h,
                addr: addr,
+               data: data,
                tasks: VecDeque::new(),
-               state: State::Handshake(
-                   Server::handshake(IoWrapper{unread: Some(buf), inner: io})),
+               state: state,
                keepalive_timer: None,
+               requests: 0,
         }
     }
 
@@ -83,6 +208,13 @@ impl<T, H> Http2<T, H>
                 }
             }
 
+            // server shutdown, send GOAWAY with last processed stream id
+            if self.settings.is_shutdown() && !self.flags.contains(Flags::GOAWAY) {
+                trace!("Server shutdown, close connection");
+                self.flags.insert(Flags::GOAWAY);
+                server.graceful_shutdown();
+            }
+
             loop {
                 let mut not_ready = true;
 
@@ -100,7 +232,17 @@ impl<T, H> Http2<T, H>
                                 }
                                 not_ready = false;
                             },
-                            Ok(Async::NotReady) => (),
+                            Ok(Async::NotReady) => {
+                                // client does not read response
+                                let progress = item.stream.take_progress();
+                                if item.write_timeout.expired(item.stream.is_pending(), progress) {
+                                    info!("Write timeout, reset stream from {:?}", self.addr);
+                                    item.flags.insert(EntryFlags::EOF);
+                                    item.flags.insert(EntryFlags::ERROR);
+                                    item.stream.reset(Reason::CANCEL);
+                                    item.task.disconnected();
+                                }
+                            },
                             Err(err) => {
                                 error!("Unhandled error: {}", err);
                                 item.flags.insert(EntryFlags::EOF);
@@ -154,7 +296,18 @@ impl<T, H> Http2<T, H>
                             self.keepalive_timer.take();
 
                             self.tasks.push_back(
-                                Entry::new(parts, body, resp, self.addr, &self.settings));
+                                Entry::new(parts, body, resp,
+                                           self.addr, &self.data, &self.settings));
+
+                            // requests limit reached, send GOAWAY
+                            self.requests += 1;
+                            if self.requests == self.settings.get_max_requests() &&
+                                !self.flags.contains(Flags::GOAWAY)
+                            {
+                                trace!("Requests limit reached, close connection");
+                                self.flags.insert(Flags::GOAWAY);
+                                server.graceful_shutdown();
+                            }
                         }
                         Ok(Async::NotReady) => {
                             // start keep-alive timer
@@ -200,6 +353,38 @@ impl<T, H> Http2<T, H>
             }
         }
 
+        // h2c upgrade, upgrade request becomes stream 1,
+        // it is injected right after client preface
+        let preface = if let State::Upgrade(ref mut io, ref mut buf, _, _) = self.state {
+            match read_preface(io, buf) {
+                Ok(Async::Ready(valid)) => Some(valid),
+                Ok(Async::NotReady) =>
+                    return Ok(Async::NotReady),
+                Err(err) => {
+                    trace!("Error handling connection: {}", err);
+                    return Err(())
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(valid) = preface {
+            if let State::Upgrade(mut io, mut unread, settings, frames) =
+                mem::replace(&mut self.state, State::Empty)
+            {
+                // invalid preface is handled by http/2 handshake
+                if valid {
+                    unread = merge_settings(&unread, &settings);
+                    unread.extend_from_slice(&frames);
+                }
+                if let Some(rest) = io.unread.take() {
+                    unread.extend_from_slice(&rest);
+                }
+                io.unread = Some(Bytes::from(unread));
+                self.state = State::Handshake(self.settings.h2_settings().handshake(io));
+            }
+        }
+
         // handshake
         self.state = if let State::Handshake(ref mut handshake) = self.state {
             match handshake.poll() {
@@ -227,6 +412,7 @@ bitflags! {
         const REOF = 0b0000_0010;
         const ERROR = 0b0000_0100;
         const FINISHED = 0b0000_1000;
+        const TRAILERS = 0b0001_0000;
     }
 }
 
@@ -237,6 +423,7 @@ struct Entry {
     stream: H2Writer,
     capacity: usize,
     flags: EntryFlags,
+    write_timeout: WriteTimeout,
 }
 
 impl Entry {
@@ -244,6 +431,7 @@ impl Entry {
               recv: RecvStream,
               resp: Respond<Bytes>,
               addr: Option<SocketAddr>,
+              data: &ConnectionData,
               settings: &Rc<WorkerSettings<H>>) -> Entry
         where H: HttpHandler + 'static
     {
@@ -259,6 +447,7 @@ impl Entry {
         msg.get_mut().addr = addr;
 
         let mut req = HttpRequest::from_message(msg);
+        data.apply(req.extensions());
 
         // Payload sender
         let psender = PayloadType::new(req.headers(), psender);
@@ -281,21 +470,42 @@ impl Entry {
                stream: H2Writer::new(resp, settings.get_shared_bytes()),
                flags: EntryFlags::empty(),
                capacity: 0,
+               write_timeout: WriteTimeout::new(settings.get_write_timeout()),
         }
     }
 
     fn poll_payload(&mut self) {
         if !self.flags.contains(EntryFlags::REOF) {
-            match self.recv.poll() {
-                Ok(Async::Ready(Some(chunk))) => {
-                    self.payload.feed_data(chunk);
-                },
-                Ok(Async::Ready(None)) => {
-                    self.flags.insert(EntryFlags::REOF);
-                },
-                Ok(Async::NotReady) => (),
-                Err(err) => {
-                    self.payload.set_error(PayloadError::Http2(err))
+            if !self.flags.contains(EntryFlags::TRAILERS) {
+                match self.recv.poll() {
+                    Ok(Async::Ready(Some(chunk))) => {
+                        self.payload.feed_data(chunk);
+                    },
+                    Ok(Async::Ready(None)) => {
+                        self.flags.insert(EntryFlags::TRAILERS);
+                    },
+                    Ok(Async::NotReady) => (),
+                    Err(err) => {
+                        self.payload.set_error(PayloadError::Http2(err))
+                    }
+                }
+            }
+
+            // payload is complete after trailers are received
+            if self.flags.contains(EntryFlags::TRAILERS) {
+                match self.recv.poll_trailers() {
+                    Ok(Async::Ready(trailers)) => {
+                        self.flags.insert(EntryFlags::REOF);
+                        if let Some(trailers) = trailers {
+                            self.payload.set_trailers(trailers);
+                        }
+                        self.payload.feed_eof();
+                    },
+                    Ok(Async::NotReady) => (),
+                    Err(err) => {
+                        self.flags.insert(EntryFlags::REOF);
+                        self.payload.set_error(PayloadError::Http2(err))
+                    }
                 }
             }
 
@@ -318,16 +528,17 @@ struct IoWrapper<T> {
 impl<T: Read> Read for IoWrapper<T> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         if let Some(mut bytes) = self.unread.take() {
-            let size = cmp::min(buf.len(), bytes.len());
-            buf[..size].copy_from_slice(&bytes[..size]);
-            if bytes.len() > size {
-                bytes.split_to(size);
-                self.unread = Some(bytes);
+            if !bytes.is_empty() {
+                let size = cmp::min(buf.len(), bytes.len());
+                buf[..size].copy_from_slice(&bytes[..size]);
+                if bytes.len() > size {
+                    bytes.split_to(size);
+                    self.unread = Some(bytes);
+                }
+                return Ok(size)
             }
-            Ok(size)
-        } else {
-            self.inner.read(buf)
         }
+        self.inner.read(buf)
     }
 }
 
@@ -354,3 +565,276 @@ impl<T: AsyncWrite + 'static> AsyncWrite for IoWrapper<T> {
         self.inner.write_buf(buf)
     }
 }
+
+/// Read client preface and following SETTINGS frame.
+///
+/// Resolves to `false` if stream does not start with valid preface.
+fn read_preface<T: Read>(io: &mut T, buf: &mut Vec<u8>) -> Poll<bool, io::Error> {
+    loop {
+        let size = match preface_size(buf) {
+            Some(size) => size,
+            None => return Ok(Async::Ready(false)),
+        };
+        if buf.len() >= size {
+            return Ok(Async::Ready(true))
+        }
+        let pos = buf.len();
+        buf.resize(size, 0);
+        match io.read(&mut buf[pos..]) {
+            Ok(0) => {
+                buf.truncate(pos);
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof, "Connection closed before http/2 preface"))
+            }
+            Ok(n) => buf.truncate(pos + n),
+            Err(e) => {
+                buf.truncate(pos);
+                if e.kind() == io::ErrorKind::WouldBlock {
+                    return Ok(Async::NotReady)
+                }
+                return Err(e)
+            }
+        }
+    }
+}
+
+/// Expected size of client preface with SETTINGS frame,
+/// `None` if buffer content is not valid preface
+fn preface_size(buf: &[u8]) -> Option<usize> {
+    let len = cmp::min(buf.len(), PREFACE.len());
+    if buf[..len] != PREFACE[..len] {
+        return None
+    }
+    let head = PREFACE.len() + FRAME_HEADER_SIZE;
+    if buf.len() < head {
+        return Some(head)
+    }
+    let frame = &buf[PREFACE.len()..];
+    let size = (frame[0] as usize) << 16 | (frame[1] as usize) << 8 | frame[2] as usize;
+    if frame[3] != FRAME_SETTINGS || size > DEFAULT_MAX_FRAME_SIZE {
+        None
+    } else {
+        Some(head + size)
+    }
+}
+
+/// Decode `HTTP2-Settings` header value, base64url encoded payload
+/// of SETTINGS frame. Returns `None` if value is not valid.
+pub(crate) fn decode_settings(value: &[u8]) -> Option<Vec<u8>> {
+    let len = value.iter().rposition(|ch| *ch != b'=').map(|pos| pos + 1).unwrap_or(0);
+    let mut buf = Vec::with_capacity(len * 3 / 4);
+    let (mut acc, mut bits) = (0u32, 0);
+    for ch in &value[..len] {
+        let val = match *ch {
+            b'A'...b'Z' => ch - b'A',
+            b'a'...b'z' => ch - b'a' + 26,
+            b'0'...b'9' => ch - b'0' + 52,
+            b'-' => 62,
+            b'_' => 63,
+            _ => return None,
+        };
+        acc = acc << 6 | u32::from(val);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            buf.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    // every setting is 6 bytes long
+    if buf.len() % 6 != 0 || buf.len() > DEFAULT_MAX_FRAME_SIZE {
+        None
+    } else {
+        Some(buf)
+    }
+}
+
+/// Prepend upgrade settings to payload of client's SETTINGS frame.
+///
+/// Upgrade settings are acknowledged implicitly, RFC 7540 section 3.2.1,
+/// single frame gets single ACK. Settings are processed in order, so
+/// values of client preface take precedence.
+fn merge_settings(preface: &[u8], settings: &[u8]) -> Vec<u8> {
+    let head = PREFACE.len() + FRAME_HEADER_SIZE;
+    let len = preface.len() - head + settings.len();
+    let mut buf = Vec::with_capacity(preface.len() + settings.len());
+    buf.extend_from_slice(PREFACE);
+    buf.extend_from_slice(&[(len >> 16) as u8, (len >> 8) as u8, len as u8]);
+    buf.extend_from_slice(&preface[PREFACE.len() + 3..head]);
+    buf.extend_from_slice(settings);
+    buf.extend_from_slice(&preface[head..]);
+    buf
+}
+
+/// Encode `h2c` upgrade request as HEADERS frame of stream 1.
+///
+/// Header fields are encoded as literals without indexing,
+/// so hpack state of connection stays untouched.
+fn upgrade_frames(req: &HttpRequest) -> Vec<u8> {
+    let mut block = Vec::new();
+    hpack_literal(&mut block, b":method", req.method().as_str().as_bytes());
+    hpack_literal(&mut block, b":scheme", b"http");
+    if let Some(host) = req.headers().get(header::HOST) {
+        hpack_literal(&mut block, b":authority", host.as_bytes());
+    }
+    let mut path = req.path().to_owned();
+    if !req.query_string().is_empty() {
+        path.push('?');
+        path.push_str(req.query_string());
+    }
+    hpack_literal(&mut block, b":path", path.as_bytes());
+
+    for (name, value) in req.headers() {
+        // connection-specific headers are not allowed in http/2
+        match name.as_str() {
+            "connection" | "upgrade" | "http2-settings" | "host" | "keep-alive" |
+            "proxy-connection" | "transfer-encoding" | "te" => continue,
+            name => hpack_literal(&mut block, name.as_bytes(), value.as_bytes()),
+        }
+    }
+
+    // upgrade request has no body, stream is half-closed
+    let mut frames = Vec::with_capacity(block.len() + FRAME_HEADER_SIZE);
+    let chunks: Vec<_> = block.chunks(DEFAULT_MAX_FRAME_SIZE).collect();
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let (kind, mut flags) = if idx == 0 {
+            (FRAME_HEADERS, FLAG_END_STREAM)
+        } else {
+            (FRAME_CONTINUATION, 0)
+        };
+        if idx == chunks.len() - 1 {
+            flags |= FLAG_END_HEADERS;
+        }
+        let len = chunk.len();
+        frames.extend_from_slice(
+            &[(len >> 16) as u8, (len >> 8) as u8, len as u8, kind, flags, 0, 0, 0, 1]);
+        frames.extend_from_slice(chunk);
+    }
+    frames
+}
+
+/// Literal header field without indexing, new name
+fn hpack_literal(dst: &mut Vec<u8>, name: &[u8], value: &[u8]) {
+    dst.push(0);
+    hpack_string(dst, name);
+    hpack_string(dst, value);
+}
+
+/// String literal without huffman encoding
+fn hpack_string(dst: &mut Vec<u8>, s: &[u8]) {
+    // length with 7-bit prefix
+    let mut len = s.len();
+    if len < 127 {
+        dst.push(len as u8);
+    } else {
+        dst.push(127);
+        len -= 127;
+        while len >= 128 {
+            dst.push((len % 128 + 128) as u8);
+            len /= 128;
+        }
+        dst.push(len as u8);
+    }
+    dst.extend_from_slice(s);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use http::{Method, Uri, Version, HeaderMap};
+    use http::header::HeaderValue;
+    use super::*;
+
+    #[test]
+    fn test_settings_limits() {
+        let _ = Http2Settings::default()
+            .initial_window_size(MAX_WINDOW_SIZE)
+            .max_frame_size(16_384)
+            .max_frame_size(MAX_MAX_FRAME_SIZE);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_settings_window_size() {
+        let _ = Http2Settings::default().initial_window_size(MAX_WINDOW_SIZE + 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_settings_frame_size() {
+        let _ = Http2Settings::default().max_frame_size(16_383);
+    }
+
+    #[test]
+    fn test_preface_size() {
+        assert_eq!(preface_size(b""), Some(33));
+        assert_eq!(preface_size(b"PRI * HTTP/2.0"), Some(33));
+        assert_eq!(preface_size(b"GET / HTTP/1.1"), None);
+
+        let mut buf = PREFACE.to_vec();
+        buf.extend_from_slice(&[0, 0, 12, FRAME_SETTINGS, 0, 0, 0, 0, 0]);
+        assert_eq!(preface_size(&buf), Some(45));
+
+        let mut buf = PREFACE.to_vec();
+        buf.extend_from_slice(&[0, 0, 12, FRAME_HEADERS, 0, 0, 0, 0, 1]);
+        assert_eq!(preface_size(&buf), None);
+    }
+
+    #[test]
+    fn test_hpack_string() {
+        let mut buf = Vec::new();
+        hpack_string(&mut buf, b"h2c");
+        assert_eq!(buf, b"\x03h2c");
+
+        let mut buf = Vec::new();
+        hpack_string(&mut buf, &[b'a'; 1337]);
+        assert_eq!(&buf[..3], &[127, 186, 9]);
+        assert_eq!(buf.len(), 1340);
+    }
+
+    #[test]
+    fn test_decode_settings() {
+        // SETTINGS_MAX_CONCURRENT_STREAMS = 100, SETTINGS_INITIAL_WINDOW_SIZE = 65535
+        assert_eq!(decode_settings(b"AAMAAABkAAQAAP__").unwrap(),
+                   &[0, 3, 0, 0, 0, 100, 0, 4, 0, 0, 255, 255]);
+        assert_eq!(decode_settings(b"AAMAAABk").unwrap(), &[0, 3, 0, 0, 0, 100]);
+        assert_eq!(decode_settings(b"").unwrap(), &[]);
+        assert!(decode_settings(b"AAMAAABkAAQAAP//").is_none());
+        assert!(decode_settings(b"AAMA").is_none());
+    }
+
+    #[test]
+    fn test_merge_settings() {
+        let mut preface = PREFACE.to_vec();
+        preface.extend_from_slice(&[0, 0, 6, FRAME_SETTINGS, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 1]);
+        let buf = merge_settings(&preface, &[0, 3, 0, 0, 0, 100]);
+
+        assert_eq!(&buf[..PREFACE.len()], PREFACE);
+        assert_eq!(&buf[PREFACE.len()..],
+                   &[0, 0, 12, FRAME_SETTINGS, 0, 0, 0, 0, 0,
+                     0, 3, 0, 0, 0, 100, 0, 4, 0, 0, 0, 1]);
+        assert_eq!(preface_size(&buf), Some(buf.len()));
+    }
+
+    #[test]
+    fn test_upgrade_frames() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_static("localhost"));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("h2c"));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+        let req = HttpRequest::new(
+            Method::GET, Uri::from_str("/index.html?q=1").unwrap(),
+            Version::HTTP_11, headers, None);
+
+        let frames = upgrade_frames(&req);
+        let len = frames.len() - FRAME_HEADER_SIZE;
+        assert_eq!(&frames[..FRAME_HEADER_SIZE],
+                   &[0, 0, len as u8, FRAME_HEADERS,
+                     FLAG_END_STREAM | FLAG_END_HEADERS, 0, 0, 0, 1]);
+        assert_eq!(&frames[FRAME_HEADER_SIZE..FRAME_HEADER_SIZE + 13],
+                   b"\x00\x07:method\x03GET");
+        assert!(frames.windows(22).any(|w| w == b"\x05:path\x0f/index.html?q=1"));
+        assert!(frames.windows(11).any(|w| w == b"\x06accept\x03*/*"));
+        assert!(!frames.windows(7).any(|w| w == b"upgrade"));
+    }
+}
@@ -11,7 +11,7 @@ use body::Body;
 use helpers::SharedBytes;
 use encoding::PayloadEncoder;
 use httprequest::HttpMessage;
-use httpresponse::HttpResponse;
+use httpresponse::{HttpResponse, Trailers};
 use h1writer::{Writer, WriterState};
 
 const CHUNK_SIZE: usize = 16_384;
@@ -32,6 +32,8 @@ pub(crate) struct H2Writer {
     flags: Flags,
     written: u64,
     buffer: SharedBytes,
+    progress: bool,
+    trailers: Option<Trailers>,
 }
 
 impl H2Writer {
@@ -44,9 +46,23 @@ impl H2Writer {
             flags: Flags::empty(),
             written: 0,
             buffer: buf,
+            progress: false,
+            trailers: None,
         }
     }
 
+    /// Writer has data which is not sent to the stream
+    pub fn is_pending(&self) -> bool {
+        self.encoder.len() > 0
+    }
+
+    /// Some data has been sent since last call
+    pub fn take_progress(&mut self) -> bool {
+        let progress = self.progress;
+        self.progress = false;
+        progress
+    }
+
     pub fn reset(&mut self, reason: Reason) {
         if let Some(mut stream) = self.stream.take() {
             stream.send_reset(reason)
@@ -63,7 +79,11 @@ impl H2Writer {
 
             if buffer.is_empty() {
                 if self.flags.contains(Flags::EOF) {
-                    let _ = stream.send_data(Bytes::new(), true);
+                    if let Some(trailers) = self.trailers.take() {
+                        let _ = stream.send_trailers(trailers.take());
+                    } else {
+                        let _ = stream.send_data(Bytes::new(), true);
+                    }
                 }
                 return Ok(WriterState::Done)
             }
@@ -83,8 +103,11 @@ impl H2Writer {
                     Ok(Async::Ready(Some(cap))) => {
                         let len = buffer.len();
                         let bytes = buffer.split_to(cmp::min(cap, len));
-                        let eof = buffer.is_empty() && self.flags.contains(Flags::EOF);
+                        // trailers close the stream
+                        let eof = buffer.is_empty() && self.flags.contains(Flags::EOF) &&
+                            self.trailers.is_none();
                         self.written += bytes.len() as u64;
+                        self.progress = true;
 
                         if let Err(err) = stream.send_data(bytes.freeze(), eof) {
                             return Err(io::Error::new(io::ErrorKind::Other, err))
@@ -92,6 +115,13 @@ impl H2Writer {
                             let cap = cmp::min(buffer.len(), CHUNK_SIZE);
                             stream.reserve_capacity(cap);
                         } else {
+                            if self.flags.contains(Flags::EOF) {
+                                if let Some(trailers) = self.trailers.take() {
+                                    if let Err(err) = stream.send_trailers(trailers.take()) {
+                                        return Err(io::Error::new(io::ErrorKind::Other, err))
+                                    }
+                                }
+                            }
                             return Ok(WriterState::Done)
                         }
                     }
@@ -135,11 +165,13 @@ impl Writer for H2Writer {
                 DATE, HeaderValue::try_from(bytes.freeze()).unwrap());
         }
 
+        self.trailers = msg.trailers().cloned();
+
         let mut resp = Response::new(());
         *resp.status_mut() = msg.status();
         *resp.version_mut() = Version::HTTP_2;
         for (key, value) in msg.headers().iter() {
-            resp.headers_mut().insert(key, value.clone());
+            resp.headers_mut().append(key, value.clone());
         }
 
         match *msg.body() {
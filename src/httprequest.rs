@@ -18,7 +18,8 @@ use payload::Payload;
 use json::JsonBody;
 use multipart::Multipart;
 use helpers::SharedHttpMessage;
-use error::{ParseError, UrlGenerationError, CookieParseError, HttpRangeError, UrlencodedError};
+use error::{ParseError, UrlGenerationError, CookieParseError, HttpRangeError,
+            UrlencodedError, ExpectError};
 
 
 pub struct HttpMessage {
@@ -151,7 +152,7 @@ impl<S> HttpRequest<S> {
 
     #[inline(always)]
     #[cfg_attr(feature = "cargo-clippy", allow(mut_from_ref, inline_always))]
-    fn as_ref(&self) -> &HttpMessage {
+    pub(crate) fn as_ref(&self) -> &HttpMessage {
         self.0.get_ref()
     }
 
@@ -266,9 +267,12 @@ impl<S> HttpRequest<S> {
     /// Peer socket address
     ///
     /// Peer address is actuall socket address, if proxy is used in front of
-    /// actix http server, then peer address would be address of this proxy.
+    /// actix http server, then peer address would be address of this proxy,
+    /// unless proxy sends PROXY protocol header, see `HttpServer::proxy_protocol()`.
     ///
     /// To get client connection information `connection_info()` method should be used.
+    ///
+    /// Connections accepted on unix domain socket do not have peer address.
     #[inline]
     pub fn peer_addr(&self) -> Option<&SocketAddr> {
         self.as_ref().addr.as_ref()
@@ -387,6 +391,28 @@ impl<S> HttpRequest<S> {
         }
     }
 
+    /// Check if client expects `100 Continue` before sending request payload.
+    ///
+    /// `Expect` header is ignored for HTTP/1.0 requests.
+    pub fn expect_continue(&self) -> Result<bool, ExpectError> {
+        if self.version() != Version::HTTP_11 {
+            return Ok(false)
+        }
+        if let Some(expect) = self.headers().get(header::EXPECT) {
+            if let Ok(s) = expect.to_str() {
+                if s.trim().eq_ignore_ascii_case("100-continue") {
+                    Ok(true)
+                } else {
+                    Err(ExpectError::UnknownExpect)
+                }
+            } else {
+                Err(ExpectError::Encoding)
+            }
+        } else {
+            Ok(false)
+        }
+    }
+
     /// Parses Range HTTP header string as per RFC 2616.
     /// `size` is full size of response (file).
     pub fn range(&self, size: u64) -> Result<Vec<HttpRange>, HttpRangeError> {
@@ -408,6 +434,13 @@ impl<S> HttpRequest<S> {
         msg.payload.as_ref().unwrap()
     }
 
+    /// Trailer headers of chunked http/1.1 or http/2 request payload.
+    ///
+    /// Trailers are available after payload is received completely.
+    pub fn trailers(&self) -> Option<HeaderMap> {
+        self.as_ref().payload.as_ref().and_then(|payload| payload.trailers())
+    }
+
     /// Returns mutable reference to the associated http payload.
     #[inline]
     pub fn payload_mut(&mut self) -> &mut Payload {
@@ -719,9 +752,9 @@ mod tests {
 
         let mut resource = Resource::<()>::default();
         resource.name("index");
-        let mut map = HashMap::new();
-        map.insert(Pattern::new("index", "/{key}/"), Some(resource));
-        let (router, _) = Router::new("", ServerSettings::default(), map);
+        let mut map = Vec::new();
+        map.push((Pattern::new("index", "/{key}/"), Some(resource)));
+        let (router, _) = Router::new("", ServerSettings::default(), map).unwrap();
         assert!(router.recognize(&mut req).is_some());
 
         assert_eq!(req.match_info().get("key"), Some("value"));
@@ -746,6 +779,28 @@ mod tests {
         assert!(req.chunked().is_err());
     }
 
+    #[test]
+    fn test_expect_continue() {
+        let req = HttpRequest::default();
+        assert!(!req.expect_continue().unwrap());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::EXPECT, header::HeaderValue::from_static("100-Continue"));
+        let req = HttpRequest::new(
+            Method::PUT, Uri::from_str("/").unwrap(), Version::HTTP_11, headers.clone(), None);
+        assert!(req.expect_continue().unwrap());
+
+        let req = HttpRequest::new(
+            Method::PUT, Uri::from_str("/").unwrap(), Version::HTTP_10, headers, None);
+        assert!(!req.expect_continue().unwrap());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::EXPECT, header::HeaderValue::from_static("200-ok"));
+        let req = HttpRequest::new(
+            Method::PUT, Uri::from_str("/").unwrap(), Version::HTTP_11, headers, None);
+        assert_eq!(req.expect_continue().err().unwrap(), ExpectError::UnknownExpect);
+    }
+
     impl PartialEq for UrlencodedError {
         fn eq(&self, other: &UrlencodedError) -> bool {
             match *self {
@@ -821,9 +876,9 @@ mod tests {
 
         let mut resource = Resource::<()>::default();
         resource.name("index");
-        let mut map = HashMap::new();
-        map.insert(Pattern::new("index", "/user/{name}.{ext}"), Some(resource));
-        let (router, _) = Router::new("/", ServerSettings::default(), map);
+        let mut map = Vec::new();
+        map.push((Pattern::new("index", "/user/{name}.{ext}"), Some(resource)));
+        let (router, _) = Router::new("/", ServerSettings::default(), map).unwrap();
         assert!(router.has_route("/user/test.html"));
         assert!(!router.has_route("/test/unknown"));
 
@@ -850,9 +905,9 @@ mod tests {
 
         let mut resource = Resource::<()>::default();
         resource.name("index");
-        let mut map = HashMap::new();
-        map.insert(Pattern::new("index", "/user/{name}.{ext}"), Some(resource));
-        let (router, _) = Router::new("/prefix/", ServerSettings::default(), map);
+        let mut map = Vec::new();
+        map.push((Pattern::new("index", "/user/{name}.{ext}"), Some(resource)));
+        let (router, _) = Router::new("/prefix/", ServerSettings::default(), map).unwrap();
         assert!(router.has_route("/user/test.html"));
         assert!(!router.has_route("/prefix/user/test.html"));
 
@@ -869,9 +924,9 @@ mod tests {
 
         let mut resource = Resource::<()>::default();
         resource.name("index");
-        let mut map = HashMap::new();
-        map.insert(Pattern::new("youtube", "https://youtube.com/watch/{video_id}"), None);
-        let (router, _) = Router::new::<()>("", ServerSettings::default(), map);
+        let mut map = Vec::new();
+        map.push((Pattern::new("youtube", "https://youtube.com/watch/{video_id}"), None));
+        let (router, _) = Router::new::<()>("", ServerSettings::default(), map).unwrap();
         assert!(!router.has_route("https://youtube.com/watch/unknown"));
 
         let req = req.with_state(Rc::new(()), router);
@@ -1,5 +1,6 @@
 //! Pieces pertaining to the HTTP response.
 use std::{mem, str, fmt};
+use std::rc::Rc;
 use std::cell::RefCell;
 use std::convert::Into;
 use std::collections::VecDeque;
@@ -29,6 +30,59 @@ pub enum ConnectionType {
     Upgrade,
 }
 
+/// Response trailer headers
+///
+/// Trailers are sent after streaming response body, in last chunk
+/// of chunked http/1.1 response or in trailing HEADERS frame of http/2 stream.
+/// Handle is cheap to clone, body stream can keep a clone and set trailers
+/// while body is generated, for example checksum of the body.
+#[derive(Debug, Clone, Default)]
+pub struct Trailers(Rc<RefCell<HeaderMap>>);
+
+impl Trailers {
+    /// Create empty trailers
+    pub fn new() -> Trailers {
+        Trailers::default()
+    }
+
+    /// Set trailer header
+    ///
+    /// Fields which are not allowed in trailers, like `Content-Length`
+    /// or `Host`, are ignored.
+    pub fn insert(&self, name: HeaderName, value: HeaderValue) {
+        if forbidden_trailer(&name) {
+            debug!("Header field is not allowed in trailers: {}", name);
+        } else {
+            self.0.borrow_mut().insert(name, value);
+        }
+    }
+
+    /// Take trailer headers for sending
+    pub(crate) fn take(&self) -> HeaderMap {
+        mem::replace(&mut *self.0.borrow_mut(), HeaderMap::new())
+    }
+}
+
+/// Header fields which must not be sent in trailers, RFC 7230 section 4.1.2
+pub(crate) fn forbidden_trailer(name: &HeaderName) -> bool {
+    match name.as_str() {
+        // message framing and routing
+        "transfer-encoding" | "content-length" | "host" | "trailer" | "te" |
+        "connection" | "keep-alive" | "upgrade" | "proxy-connection" |
+        // request modifiers
+        "cache-control" | "expect" | "max-forwards" | "pragma" | "range" |
+        "if-match" | "if-none-match" | "if-modified-since" | "if-unmodified-since" |
+        "if-range" |
+        // authentication
+        "authorization" | "proxy-authorization" | "www-authenticate" |
+        "proxy-authenticate" | "cookie" | "set-cookie" |
+        // response control data and payload processing
+        "age" | "expires" | "date" | "location" | "retry-after" | "vary" | "warning" |
+        "content-encoding" | "content-type" | "content-range" => true,
+        _ => false,
+    }
+}
+
 /// An HTTP Response
 pub struct HttpResponse(Option<Box<InnerHttpResponse>>);
 
@@ -137,6 +191,12 @@ impl HttpResponse {
         self
     }
 
+    /// Response trailers
+    #[inline]
+    pub fn trailers(&self) -> Option<&Trailers> {
+        self.get_ref().trailers.as_ref()
+    }
+
     /// Connection upgrade status
     pub fn upgrade(&self) -> bool {
         self.get_ref().connection_type == Some(ConnectionType::Upgrade)
@@ -320,6 +380,65 @@ impl HttpResponseBuilder {
         self.connection_type(ConnectionType::Close)
     }
 
+    /// Add preload hint for resource.
+    ///
+    /// Adds `Link: <path>; rel=preload` header, so client can start loading
+    /// resource before it parses response body. This is a hint only,
+    /// resource is not pushed to the client.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// # use actix_web::*;
+    /// # use actix_web::httpcodes::*;
+    /// #
+    /// fn index(req: HttpRequest) -> Result<HttpResponse> {
+    ///     Ok(HTTPOk.build()
+    ///         .preload("/static/style.css")
+    ///         .finish()?)
+    /// }
+    /// fn main() {}
+    /// ```
+    pub fn preload(&mut self, path: &str) -> &mut Self {
+        self.header(header::LINK, format!("<{}>; rel=preload", path).as_str())
+    }
+
+    /// Send trailer headers after streaming body.
+    ///
+    /// Enables chunked transfer encoding. Trailers have to be set before body
+    /// stream completes.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// # extern crate futures;
+    /// # use actix_web::*;
+    /// # use actix_web::httpcodes::*;
+    /// use futures::{Async, Stream, stream};
+    /// use actix_web::headers::Trailers;
+    ///
+    /// fn index(req: HttpRequest) -> Result<HttpResponse> {
+    ///     let trailers = Trailers::new();
+    ///     let tx = trailers.clone();
+    ///
+    ///     // body stream sets trailer after last chunk
+    ///     let body = req.payload().clone().stream()
+    ///         .chain(stream::poll_fn(move || {
+    ///             tx.insert("x-status".parse().unwrap(), "done".parse().unwrap());
+    ///             Ok(Async::Ready(None))
+    ///         }));
+    ///     Ok(HTTPOk.build()
+    ///         .trailers(trailers)
+    ///         .body(Body::Streaming(Box::new(body)))?)
+    /// }
+    /// fn main() {}
+    /// ```
+    pub fn trailers(&mut self, trailers: Trailers) -> &mut Self {
+        if let Some(parts) = parts(&mut self.response, &self.err) {
+            parts.trailers = Some(trailers);
+            parts.chunked = true;
+        }
+        self
+    }
+
     /// Enables automatic chunked transfer encoding
     #[inline]
     pub fn chunked(&mut self) -> &mut Self {
@@ -614,6 +733,7 @@ struct InnerHttpResponse {
     connection_type: Option<ConnectionType>,
     response_size: u64,
     error: Option<Error>,
+    trailers: Option<Trailers>,
 }
 
 impl InnerHttpResponse {
@@ -631,6 +751,7 @@ impl InnerHttpResponse {
             connection_type: None,
             response_size: 0,
             error: None,
+            trailers: None,
         }
     }
 }
@@ -683,6 +804,7 @@ impl Pool {
                 inner.connection_type = None;
                 inner.response_size = 0;
                 inner.error = None;
+                inner.trailers = None;
                 v.push_front(inner);
             }
         })
@@ -761,6 +883,30 @@ mod tests {
         assert!(!resp.keep_alive().unwrap())
     }
 
+    #[test]
+    fn test_trailers() {
+        let trailers = Trailers::new();
+        trailers.insert(HeaderName::from_static("checksum"), HeaderValue::from_static("abc"));
+        trailers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("10"));
+        trailers.insert(header::HOST, HeaderValue::from_static("localhost"));
+
+        let resp = HttpResponse::build(StatusCode::OK)
+            .trailers(trailers.clone()).finish().unwrap();
+        assert!(resp.chunked());
+
+        let headers = resp.trailers().unwrap().take();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.get("checksum").unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_preload() {
+        let resp = HttpResponse::build(StatusCode::OK)
+            .preload("/style.css").preload("/app.js").finish().unwrap();
+        let links: Vec<_> = resp.headers().get_all(header::LINK).iter().collect();
+        assert_eq!(links, vec!["</style.css>; rel=preload", "</app.js>; rel=preload"]);
+    }
+
     #[test]
     fn test_content_type() {
         let resp = HttpResponse::build(StatusCode::OK)
@@ -1,7 +1,13 @@
+use std::rc::Rc;
 use std::str::FromStr;
 use http::header::{self, HeaderName};
 use httprequest::HttpRequest;
 
+#[cfg(feature="alpn")]
+use openssl::nid;
+#[cfg(feature="alpn")]
+use openssl::x509::X509Ref;
+
 const X_FORWARDED_FOR: &str = "X-FORWARDED-FOR";
 const X_FORWARDED_HOST: &str = "X-FORWARDED-HOST";
 const X_FORWARDED_PROTO: &str = "X-FORWARDED-PROTO";
@@ -13,8 +19,15 @@ pub struct ConnectionInfo<'a> {
     host: &'a str,
     remote: Option<&'a str>,
     peer: Option<String>,
+    certificate: Option<&'a PeerCertificate>,
+    server_name: Option<&'a str>,
 }
 
+/// Server name sent by tls client
+#[derive(Clone)]
+#[cfg_attr(not(feature="alpn"), allow(dead_code))]
+pub(crate) struct ServerName(pub String);
+
 impl<'a> ConnectionInfo<'a> {
 
     /// Create *ConnectionInfo* instance for a request.
@@ -107,11 +120,19 @@ impl<'a> ConnectionInfo<'a> {
             }
         }
 
+        // tls client certificate
+        let certificate = req.as_ref().extensions
+            .get::<Rc<PeerCertificate>>().map(|cert| cert.as_ref());
+        let server_name = req.as_ref().extensions
+            .get::<ServerName>().map(|name| name.0.as_str());
+
         ConnectionInfo {
             scheme: scheme.unwrap_or("http"),
             host: host.unwrap_or("localhost"),
             remote: remote,
             peer: peer,
+            certificate: certificate,
+            server_name: server_name,
         }
     }
 
@@ -157,6 +178,63 @@ impl<'a> ConnectionInfo<'a> {
             None
         }
     }
+
+    /// Verified tls client certificate of the connection.
+    ///
+    /// Client certificates are requested only if server is configured
+    /// with `HttpServer::client_auth()`.
+    #[inline]
+    pub fn peer_certificate(&self) -> Option<&PeerCertificate> {
+        self.certificate
+    }
+
+    /// Server name (SNI) sent by tls client during handshake.
+    #[inline]
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name
+    }
+}
+
+/// Tls client certificate
+#[derive(Debug, Clone)]
+pub struct PeerCertificate {
+    common_name: Option<String>,
+    dns_names: Vec<String>,
+    der: Vec<u8>,
+}
+
+impl PeerCertificate {
+
+    #[cfg(feature="alpn")]
+    pub(crate) fn from_x509(cert: &X509Ref) -> PeerCertificate {
+        let common_name = cert.subject_name().entries_by_nid(nid::COMMONNAME).next()
+            .and_then(|entry| entry.data().as_utf8().ok())
+            .map(|name| name.to_string());
+        let dns_names = cert.subject_alt_names()
+            .map(|names| names.iter()
+                 .filter_map(|name| name.dnsname().map(|n| n.to_owned())).collect())
+            .unwrap_or_else(Vec::new);
+        PeerCertificate {
+            common_name: common_name,
+            dns_names: dns_names,
+            der: cert.to_der().unwrap_or_else(|_| Vec::new()),
+        }
+    }
+
+    /// Common name of certificate subject
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_ref().map(|s| s.as_str())
+    }
+
+    /// DNS names from subject alternative name extension
+    pub fn dns_names(&self) -> &[String] {
+        &self.dns_names
+    }
+
+    /// DER encoded certificate
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
 }
 
 #[cfg(test)]
@@ -210,4 +288,32 @@ mod tests {
         let info = ConnectionInfo::new(&req);
         assert_eq!(info.scheme(), "https");
     }
+
+    #[test]
+    fn test_peer_certificate() {
+        let req = HttpRequest::default();
+        assert!(ConnectionInfo::new(&req).peer_certificate().is_none());
+
+        let mut req = HttpRequest::default();
+        req.extensions().insert(Rc::new(PeerCertificate {
+            common_name: Some("client".to_owned()),
+            dns_names: vec!["client.example.com".to_owned()],
+            der: vec![0x30, 0x00],
+        }));
+        let info = ConnectionInfo::new(&req);
+        let cert = info.peer_certificate().unwrap();
+        assert_eq!(cert.common_name(), Some("client"));
+        assert_eq!(cert.dns_names(), &["client.example.com".to_owned()]);
+        assert_eq!(cert.der(), &[0x30, 0x00]);
+    }
+
+    #[test]
+    fn test_server_name() {
+        let req = HttpRequest::default();
+        assert_eq!(ConnectionInfo::new(&req).server_name(), None);
+
+        let mut req = HttpRequest::default();
+        req.extensions().insert(ServerName("rust-lang.org".to_owned()));
+        assert_eq!(ConnectionInfo::new(&req).server_name(), Some("rust-lang.org"));
+    }
 }
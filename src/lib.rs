@@ -73,6 +73,11 @@ extern crate num_cpus;
 extern crate h2 as http2;
 #[macro_use] extern crate actix;
 
+#[cfg(unix)]
+extern crate mio_uds;
+#[cfg(unix)]
+extern crate tokio_uds;
+
 #[cfg(test)]
 #[macro_use] extern crate serde_derive;
 
@@ -110,6 +115,7 @@ mod h1;
 mod h2;
 mod h1writer;
 mod h2writer;
+mod proxy;
 
 pub mod fs;
 pub mod ws;
@@ -130,6 +136,8 @@ pub use handler::{Reply, Responder, NormalizePath, AsyncResponder};
 pub use route::Route;
 pub use resource::Resource;
 pub use server::HttpServer;
+#[cfg(feature="alpn")]
+pub use server::ClientAuth;
 pub use context::HttpContext;
 
 // re-exports
@@ -147,7 +155,7 @@ pub mod headers {
 //! Headers implementation
 
     pub use encoding::ContentEncoding;
-    pub use httpresponse::ConnectionType;
+    pub use httpresponse::{ConnectionType, Trailers};
 
     pub use cookie::Cookie;
     pub use cookie::CookieBuilder;
@@ -166,14 +174,20 @@ pub mod dev {
 //! ```
 
     pub use body::BodyStream;
-    pub use info::ConnectionInfo;
+    pub use info::{ConnectionInfo, PeerCertificate};
     pub use handler::Handler;
     pub use json::JsonBody;
     pub use router::{Router, Pattern};
-    pub use channel::{HttpChannel, HttpHandler, IntoHttpHandler};
+    pub use channel::{HttpChannel, HttpHandler, IntoHttpHandler, Connection};
     pub use param::{FromParam, Params};
     pub use httprequest::UrlEncoded;
     pub use httpresponse::HttpResponseBuilder;
 
-    pub use server::{ServerSettings, PauseServer, ResumeServer, StopServer};
+    pub use h1::Http1Limits;
+    pub use h2::Http2Settings;
+    pub use server::{ServerSettings, Dispatch, PauseServer, ResumeServer, StopServer};
+    #[cfg(unix)]
+    pub use server::HandoffServer;
+    #[cfg(feature="alpn")]
+    pub use server::ReloadSsl;
 }
@@ -5,12 +5,14 @@ use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::ops::{Deref, DerefMut};
 use bytes::{Bytes, BytesMut};
+use http::HeaderMap;
 use futures::{Future, Async, Poll, Stream};
 use futures::task::{Task, current as current_task};
 
 use body::BodyStream;
 use actix::ResponseType;
 use error::PayloadError;
+use httpresponse::forbidden_trailer;
 
 pub(crate) const DEFAULT_BUFFER_SIZE: usize = 65_536; // max buffer size 64k
 
@@ -80,6 +82,12 @@ impl Payload {
         self.inner.borrow().eof()
     }
 
+    /// Trailer headers of chunked or http/2 payload,
+    /// available after payload eof.
+    pub fn trailers(&self) -> Option<HeaderMap> {
+        self.inner.borrow().trailers.clone()
+    }
+
     /// Length of the data in this payload
     pub fn len(&self) -> usize {
         self.inner.borrow().len()
@@ -228,8 +236,17 @@ pub trait PayloadWriter {
     /// Feed bytes into a payload stream
     fn feed_data(&mut self, data: Bytes);
 
+    /// Set trailer headers, received after payload data
+    #[allow(unused_variables)]
+    fn set_trailers(&mut self, trailers: HeaderMap) {}
+
     /// Get estimated available capacity
     fn capacity(&self) -> usize;
+
+    /// Check if reading side waits for payload data
+    fn need_read(&self) -> bool {
+        false
+    }
 }
 
 /// Sender part of the payload stream
@@ -257,6 +274,18 @@ impl PayloadWriter for PayloadSender {
         }
     }
 
+    fn set_trailers(&mut self, trailers: HeaderMap) {
+        if let Some(shared) = self.inner.upgrade() {
+            let mut allowed = HeaderMap::new();
+            for (name, value) in trailers.iter() {
+                if !forbidden_trailer(name) {
+                    allowed.append(name.clone(), value.clone());
+                }
+            }
+            shared.borrow_mut().trailers = Some(allowed)
+        }
+    }
+
     fn capacity(&self) -> usize {
         if let Some(shared) = self.inner.upgrade() {
             shared.borrow().capacity()
@@ -264,6 +293,14 @@ impl PayloadWriter for PayloadSender {
             0
         }
     }
+
+    fn need_read(&self) -> bool {
+        if let Some(shared) = self.inner.upgrade() {
+            shared.borrow().task.is_some()
+        } else {
+            false
+        }
+    }
 }
 
 
@@ -275,6 +312,7 @@ struct Inner {
     task: Option<Task>,
     items: VecDeque<Bytes>,
     buf_size: usize,
+    trailers: Option<HeaderMap>,
 }
 
 impl Inner {
@@ -287,6 +325,7 @@ impl Inner {
             task: None,
             items: VecDeque::new(),
             buf_size: DEFAULT_BUFFER_SIZE,
+            trailers: None,
         }
     }
 
@@ -471,6 +510,20 @@ mod tests {
         assert_eq!(format!("{}", err), "A payload reached EOF, but is not complete.");
     }
 
+    #[test]
+    fn test_trailers() {
+        let (mut sender, payload) = Payload::new(false);
+
+        let mut trailers = HeaderMap::new();
+        trailers.insert("checksum", "abc".parse().unwrap());
+        trailers.insert("content-length", "10".parse().unwrap());
+        sender.set_trailers(trailers);
+
+        let trailers = payload.trailers().unwrap();
+        assert_eq!(trailers.get("checksum").unwrap(), "abc");
+        assert!(trailers.get("content-length").is_none());
+    }
+
     #[test]
     fn test_basic() {
         Core::new().unwrap().run(lazy(|| {
@@ -525,6 +578,23 @@ mod tests {
         })).unwrap();
     }
 
+    #[test]
+    fn test_need_read() {
+        Core::new().unwrap().run(lazy(|| {
+            let (mut sender, mut payload) = Payload::new(false);
+            assert!(!sender.need_read());
+
+            assert_eq!(Async::NotReady, payload.readany().poll().ok().unwrap());
+            assert!(sender.need_read());
+
+            sender.feed_data(Bytes::from("data"));
+            assert!(!sender.need_read());
+
+            let res: Result<(), ()> = Ok(());
+            result(res)
+        })).unwrap();
+    }
+
     #[test]
     fn test_readany() {
         Core::new().unwrap().run(lazy(|| {
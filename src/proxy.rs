@@ -0,0 +1,291 @@
+//! PROXY protocol header parsing
+//!
+//! http://www.haproxy.org/download/1.8/doc/proxy-protocol.txt
+use std::{io, str};
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use futures::{Async, Future, Poll};
+use tokio_io::AsyncRead;
+
+/// Binary header signature
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+/// Maximum length of text header, including CRLF
+const V1_MAX_LENGTH: usize = 107;
+/// Length of signature, version, family and address length fields
+const V2_HEADER_LENGTH: usize = 16;
+
+#[derive(PartialEq, Debug)]
+enum State {
+    Signature,
+    V1,
+    V2Header,
+    V2Address,
+}
+
+/// Read PROXY protocol header from stream.
+///
+/// Resolves to the stream and client address advertised by proxy.
+/// If proxy does not know client address, original peer address is used.
+/// Stream is read exactly up to the end of header, so request data
+/// stays in the stream.
+pub(crate) struct ProxyProtocol<T> {
+    io: Option<T>,
+    peer: Option<SocketAddr>,
+    buf: Vec<u8>,
+    state: State,
+}
+
+impl<T: AsyncRead> ProxyProtocol<T> {
+    pub fn new(io: T, peer: Option<SocketAddr>) -> ProxyProtocol<T> {
+        ProxyProtocol {
+            io: Some(io),
+            peer: peer,
+            buf: Vec::with_capacity(V2_HEADER_LENGTH),
+            state: State::Signature,
+        }
+    }
+
+    /// Read from stream until buffer contains `size` bytes
+    fn fill(&mut self, size: usize) -> Poll<(), io::Error> {
+        let io = self.io.as_mut().expect("Use future after completion");
+        while self.buf.len() < size {
+            let pos = self.buf.len();
+            self.buf.resize(size, 0);
+            match io.read(&mut self.buf[pos..]) {
+                Ok(0) => {
+                    self.buf.truncate(pos);
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof, "Connection closed before PROXY header"))
+                }
+                Ok(n) => self.buf.truncate(pos + n),
+                Err(e) => {
+                    self.buf.truncate(pos);
+                    if e.kind() == io::ErrorKind::WouldBlock {
+                        return Ok(Async::NotReady)
+                    }
+                    return Err(e)
+                }
+            }
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<T: AsyncRead> Future for ProxyProtocol<T> {
+    type Item = (T, Option<SocketAddr>);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            match self.state {
+                State::Signature => {
+                    // shortest text header is longer than binary signature
+                    try_ready!(self.fill(V2_SIGNATURE.len()));
+                    if self.buf == V2_SIGNATURE {
+                        self.state = State::V2Header;
+                    } else if self.buf.starts_with(b"PROXY ") {
+                        self.state = State::V1;
+                    } else {
+                        return Err(invalid("PROXY header is missing"))
+                    }
+                }
+                State::V1 => {
+                    // text header is read byte by byte up to CRLF
+                    while !self.buf.ends_with(b"\r\n") {
+                        if self.buf.len() >= V1_MAX_LENGTH {
+                            return Err(invalid("PROXY header is too long"))
+                        }
+                        let size = self.buf.len() + 1;
+                        try_ready!(self.fill(size));
+                    }
+                    let peer = parse_v1(&self.buf[..self.buf.len() - 2], self.peer)?;
+                    return Ok(Async::Ready((self.io.take().unwrap(), peer)))
+                }
+                State::V2Header => {
+                    try_ready!(self.fill(V2_HEADER_LENGTH));
+                    if self.buf[12] >> 4 != 2 {
+                        return Err(invalid("Unsupported PROXY protocol version"))
+                    }
+                    self.state = State::V2Address;
+                }
+                State::V2Address => {
+                    let len = (self.buf[14] as usize) << 8 | self.buf[15] as usize;
+                    try_ready!(self.fill(V2_HEADER_LENGTH + len));
+                    let peer = parse_v2(&self.buf, self.peer)?;
+                    return Ok(Async::Ready((self.io.take().unwrap(), peer)))
+                }
+            }
+        }
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Parse text header without CRLF
+fn parse_v1(line: &[u8], peer: Option<SocketAddr>) -> io::Result<Option<SocketAddr>> {
+    let line = str::from_utf8(line).map_err(|_| invalid("Invalid PROXY header"))?;
+    let mut parts = line.split(' ').skip(1);
+
+    match parts.next() {
+        Some("UNKNOWN") => return Ok(peer),
+        Some("TCP4") | Some("TCP6") => (),
+        _ => return Err(invalid("Unsupported PROXY protocol family")),
+    }
+    let parts: Vec<_> = parts.collect();
+    if parts.len() != 4 {
+        return Err(invalid("Invalid PROXY header"))
+    }
+    let ip = parts[0].parse::<IpAddr>().map_err(|_| invalid("Invalid PROXY source address"))?;
+    let port = parts[2].parse::<u16>().map_err(|_| invalid("Invalid PROXY source port"))?;
+    Ok(Some(SocketAddr::new(ip, port)))
+}
+
+/// Parse binary header
+fn parse_v2(buf: &[u8], peer: Option<SocketAddr>) -> io::Result<Option<SocketAddr>> {
+    let addr = &buf[V2_HEADER_LENGTH..];
+    match buf[12] & 0x0f {
+        // health check from proxy itself
+        0x00 => return Ok(peer),
+        0x01 => (),
+        _ => return Err(invalid("Unsupported PROXY protocol command")),
+    }
+    match buf[13] {
+        // TCP over IPv4
+        0x11 if addr.len() >= 12 => {
+            let ip = Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]);
+            let port = (addr[8] as u16) << 8 | addr[9] as u16;
+            Ok(Some(SocketAddr::new(IpAddr::V4(ip), port)))
+        }
+        // TCP over IPv6
+        0x21 if addr.len() >= 36 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&addr[..16]);
+            let port = (addr[32] as u16) << 8 | addr[33] as u16;
+            Ok(Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(octets)), port)))
+        }
+        0x11 | 0x21 => Err(invalid("Invalid PROXY address length")),
+        // unspecified and non tcp families
+        _ => Ok(peer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io, cmp};
+    use super::*;
+
+    struct Buffer {
+        buf: Vec<u8>,
+        pos: usize,
+    }
+
+    impl Buffer {
+        fn new(data: &[u8]) -> Buffer {
+            Buffer { buf: data.to_vec(), pos: 0 }
+        }
+    }
+
+    impl AsyncRead for Buffer {}
+    impl io::Read for Buffer {
+        fn read(&mut self, dst: &mut [u8]) -> Result<usize, io::Error> {
+            if self.pos == self.buf.len() {
+                Err(io::Error::new(io::ErrorKind::WouldBlock, ""))
+            } else {
+                let size = cmp::min(self.buf.len() - self.pos, dst.len());
+                dst[..size].copy_from_slice(&self.buf[self.pos..self.pos + size]);
+                self.pos += size;
+                Ok(size)
+            }
+        }
+    }
+
+    fn peer() -> Option<SocketAddr> {
+        Some("127.0.0.1:5000".parse().unwrap())
+    }
+
+    fn parse(data: &[u8]) -> io::Result<(Option<SocketAddr>, Vec<u8>)> {
+        match ProxyProtocol::new(Buffer::new(data), peer()).poll()? {
+            Async::Ready((io, peer)) => Ok((peer, io.buf[io.pos..].to_vec())),
+            Async::NotReady => panic!("Header is not complete"),
+        }
+    }
+
+    #[test]
+    fn test_v1_tcp4() {
+        let (peer, rest) = parse(
+            b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\nGET / HTTP/1.1\r\n").unwrap();
+        assert_eq!(peer, Some("192.168.0.1:56324".parse().unwrap()));
+        assert_eq!(rest, b"GET / HTTP/1.1\r\n");
+    }
+
+    #[test]
+    fn test_v1_tcp6() {
+        let (peer, _) = parse(b"PROXY TCP6 ::1 ::2 56324 443\r\n").unwrap();
+        assert_eq!(peer, Some("[::1]:56324".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_v1_unknown() {
+        assert_eq!(parse(b"PROXY UNKNOWN\r\n").unwrap().0, peer());
+    }
+
+    #[test]
+    fn test_v1_incomplete() {
+        let mut fut = ProxyProtocol::new(Buffer::new(b"PROXY TCP4 192.168"), peer());
+        assert!(fut.poll().unwrap().is_not_ready());
+    }
+
+    #[test]
+    fn test_v1_invalid() {
+        assert!(parse(b"GET / HTTP/1.1\r\n\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 192.168.0.1 56324 443\r\n").is_err());
+        assert!(parse(b"PROXY TCP4 192.168.0.1 192.168.0.11 port 443\r\n").is_err());
+        assert!(parse(b"PROXY UDP4 192.168.0.1 192.168.0.11 56324 443\r\n").is_err());
+
+        let mut long = b"PROXY TCP4 ".to_vec();
+        long.extend_from_slice(&[b'1'; 120]);
+        assert!(parse(&long).is_err());
+    }
+
+    #[test]
+    fn test_v2_tcp4() {
+        let mut data = V2_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0x21, 0x11, 0, 12,
+                                 192, 168, 0, 1, 192, 168, 0, 11, 0xdc, 0x04, 0x01, 0xbb]);
+        data.extend_from_slice(b"GET");
+        let (peer, rest) = parse(&data).unwrap();
+        assert_eq!(peer, Some("192.168.0.1:56324".parse().unwrap()));
+        assert_eq!(rest, b"GET");
+    }
+
+    #[test]
+    fn test_v2_tcp6() {
+        let mut data = V2_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0x21, 0x21, 0, 36]);
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2]);
+        data.extend_from_slice(&[0xdc, 0x04, 0x01, 0xbb]);
+        let (peer, _) = parse(&data).unwrap();
+        assert_eq!(peer, Some("[::1]:56324".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_v2_local() {
+        let mut data = V2_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        assert_eq!(parse(&data).unwrap().0, peer());
+    }
+
+    #[test]
+    fn test_v2_invalid() {
+        let mut data = V2_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0x11, 0x11, 0, 0]);
+        assert!(parse(&data).is_err());
+
+        let mut data = V2_SIGNATURE.to_vec();
+        data.extend_from_slice(&[0x21, 0x11, 0, 4, 192, 168, 0, 1]);
+        assert!(parse(&data).is_err());
+    }
+}
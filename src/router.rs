@@ -1,11 +1,13 @@
 use std::mem;
 use std::rc::Rc;
+use std::str::Chars;
+use std::iter::Peekable;
 use std::hash::{Hash, Hasher};
 use std::collections::HashMap;
 
-use regex::{Regex, RegexSet};
+use regex::{self, Regex};
 
-use error::UrlGenerationError;
+use error::{UrlGenerationError, RouterError};
 use resource::Resource;
 use httprequest::HttpRequest;
 use server::ServerSettings;
@@ -17,45 +19,92 @@ pub struct Router(Rc<Inner>);
 struct Inner {
     prefix: String,
     prefix_len: usize,
-    regset: RegexSet,
+    tree: Node,
     named: HashMap<String, (Pattern, bool)>,
-    patterns: Vec<Pattern>,
     srv: ServerSettings,
 }
 
 impl Router {
     /// Create new router
+    ///
+    /// Patterns are in registration order, patterns without resource
+    /// are used for url generation only. Returns error if some pattern
+    /// can never be matched or if name is used more than once.
     pub fn new<S>(prefix: &str,
                   settings: ServerSettings,
-                  map: HashMap<Pattern, Option<Resource<S>>>) -> (Router, Vec<Resource<S>>)
+                  map: Vec<(Pattern, Option<Resource<S>>)>)
+                  -> Result<(Router, Vec<Resource<S>>), RouterError>
     {
         let prefix = prefix.trim().trim_right_matches('/').to_owned();
-        let mut named = HashMap::new();
-        let mut patterns = Vec::new();
+        let mut named: HashMap<String, (Pattern, bool)> = HashMap::new();
+        let mut tree = Node::default();
         let mut resources = Vec::new();
-        let mut paths = Vec::new();
+
+        // registered paths by segment keys, and catch-all patterns, patterns
+        // ending with parameter and single segment regex patterns by parent keys
+        let mut keys: HashMap<Vec<String>, String> = HashMap::new();
+        let mut catch_all: HashMap<Vec<String>, String> = HashMap::new();
+        let mut params: HashMap<Vec<String>, String> = HashMap::new();
+        let mut single: HashMap<Vec<String>, String> = HashMap::new();
 
         for (pattern, resource) in map {
             if !pattern.name().is_empty() {
+                if let Some(&(ref prev, _)) = named.get(pattern.name()) {
+                    return Err(RouterError::Duplicate(prev.path.clone(), pattern.path.clone()))
+                }
                 let name = pattern.name().into();
                 named.insert(name, (pattern.clone(), resource.is_none()));
             }
 
             if let Some(resource) = resource {
-                paths.push(pattern.pattern().to_owned());
-                patterns.push(pattern);
+                let key: Vec<_> = pattern.segments.iter().map(|s| s.key()).collect();
+                if let Some(prev) = keys.get(&key) {
+                    return Err(RouterError::Duplicate(prev.clone(), pattern.path.clone()))
+                }
+
+                // catch-all pattern matches all paths of later custom regex
+                // patterns of the same parent segments. parameter segment is tried
+                // before custom regex, so it matches all paths of single segment regex
+                match pattern.segments.last() {
+                    Some(&Segment::Tail(ref re)) => {
+                        let parent = key[..key.len()-1].to_vec();
+                        if let Some(prev) = catch_all.get(&parent) {
+                            return Err(RouterError::Shadowed(prev.clone(), pattern.path.clone()))
+                        }
+                        if re.is_single_segment() {
+                            if let Some(prev) = params.get(&parent) {
+                                return Err(
+                                    RouterError::Shadowed(prev.clone(), pattern.path.clone()))
+                            }
+                            single.insert(parent.clone(), pattern.path.clone());
+                        }
+                        if re.is_catch_all() {
+                            catch_all.insert(parent, pattern.path.clone());
+                        }
+                    }
+                    Some(&Segment::Param(_)) => {
+                        let parent = key[..key.len()-1].to_vec();
+                        if let Some(prev) = single.get(&parent) {
+                            return Err(RouterError::Shadowed(pattern.path.clone(), prev.clone()))
+                        }
+                        params.insert(parent, pattern.path.clone());
+                    }
+                    _ => (),
+                }
+                keys.insert(key, pattern.path.clone());
+
+                tree.insert(&pattern.segments, resources.len());
                 resources.push(resource);
             }
         }
 
         let len = prefix.len();
-        (Router(Rc::new(
+        Ok((Router(Rc::new(
             Inner{ prefix: prefix,
                    prefix_len: len,
-                   regset: RegexSet::new(&paths).unwrap(),
+                   tree: tree,
                    named: named,
-                   patterns: patterns,
-                   srv: settings })), resources)
+                   srv: settings })), resources))
     }
 
     /// Router prefix
@@ -72,25 +121,17 @@ impl Router {
 
     /// Query for matched resource
     pub fn recognize<S>(&self, req: &mut HttpRequest<S>) -> Option<usize> {
-        let mut idx = None;
-        {
-            if self.0.prefix_len > req.path().len() {
-                return None
-            }
-            let path = &req.path()[self.0.prefix_len..];
-            if path.is_empty() {
-                if let Some(i) = self.0.regset.matches("/").into_iter().next() {
-                    idx = Some(i);
-                }
-            } else if let Some(i) = self.0.regset.matches(path).into_iter().next() {
-                idx = Some(i);
-            }
+        if self.0.prefix_len > req.path().len() {
+            return None
         }
+        let path: &str = unsafe{ mem::transmute(&req.path()[self.0.prefix_len..]) };
 
-        if let Some(idx) = idx {
-            let path: &str = unsafe{ mem::transmute(&req.path()[self.0.prefix_len..]) };
-            self.0.patterns[idx].update_match_info(path, req);
-            return Some(idx)
+        let mut params = Vec::new();
+        if let Some(idx) = self.0.tree.recognize(path, &mut params) {
+            for (name, value) in params {
+                req.match_info_mut().add(name, value);
+            }
+            Some(idx)
         } else {
             None
         }
@@ -103,14 +144,7 @@ impl Router {
     /// following path would be recognizable `/test/name` but `has_route()` call
     /// would return `false`.
     pub fn has_route(&self, path: &str) -> bool {
-        if path.is_empty() {
-            if self.0.regset.matches("/").into_iter().next().is_some() {
-                return true
-            }
-        } else if self.0.regset.matches(path).into_iter().next().is_some() {
-            return true
-        }
-        false
+        self.0.tree.recognize(path, &mut Vec::new()).is_some()
     }
 
     /// Build named resource path.
@@ -140,6 +174,261 @@ impl Clone for Router {
     }
 }
 
+/// Path segment matcher
+#[derive(Clone)]
+struct SegmentRegex {
+    re: Regex,
+    names: Vec<String>,
+    /// regex without capture names
+    key: String,
+}
+
+impl SegmentRegex {
+    fn new(re: &str) -> Result<SegmentRegex, regex::Error> {
+        let re = Regex::new(&format!("^{}$", re))?;
+        let names = re.capture_names()
+            .filter_map(|name| name.map(|name| name.to_owned()))
+            .collect();
+
+        let mut key = String::new();
+        let mut rest = re.as_str();
+        while let Some(pos) = rest.find("(?P<") {
+            key.push_str(&rest[..pos+1]);
+            rest = &rest[pos+4..];
+            rest = if let Some(pos) = rest.find('>') { &rest[pos+1..] } else { "" };
+        }
+        key.push_str(rest);
+
+        Ok(SegmentRegex{re: re, names: names, key: key})
+    }
+
+    /// Regex matches any text
+    fn is_catch_all(&self) -> bool {
+        self.key == "^(.*)$"
+    }
+
+    /// Regex matches single non-empty segment only, same as parameter segment does
+    fn is_single_segment(&self) -> bool {
+        !self.re.is_match("") && !matches_slash(&mut self.key.chars().peekable())
+    }
+
+    /// Match text and push captured parameters
+    fn matches<'a>(&'a self, text: &'a str, params: &mut Vec<(&'a str, &'a str)>) -> bool {
+        if let Some(captures) = self.re.captures(text) {
+            for name in &self.names {
+                if let Some(m) = captures.name(name) {
+                    params.push((name.as_str(), m.as_str()));
+                }
+            }
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Check if regex may match text with slash. Check is conservative,
+/// unknown constructs are treated as matching slash.
+fn matches_slash(chars: &mut Peekable<Chars>) -> bool {
+    while let Some(c) = chars.next() {
+        match c {
+            '.' | '/' => return true,
+            '\\' => match chars.next() {
+                Some(c) if "/SWDPp".contains(c) => return true,
+                _ => (),
+            },
+            '[' => if class_matches_slash(chars) {
+                return true
+            },
+            _ => (),
+        }
+    }
+    false
+}
+
+/// Check if character class matches slash, opening bracket is consumed
+fn class_matches_slash(chars: &mut Peekable<Chars>) -> bool {
+    let negated = chars.peek() == Some(&'^');
+    if negated {
+        chars.next();
+    }
+    let mut slash = false;
+    let mut first = true;
+    let mut prev = None;
+    loop {
+        let c = match chars.next() {
+            Some(']') if !first => break,
+            Some('\\') => match chars.next() {
+                Some(c) if !"SWDPp".contains(c) => c,
+                _ => return true,
+            },
+            Some('[') | None => return true,
+            Some(c) => c,
+        };
+        first = false;
+
+        // character range
+        if c == '-' && prev.is_some() && chars.peek().map(|c| *c != ']').unwrap_or(false) {
+            let end = match chars.next() {
+                Some('\\') | Some('[') | None => return true,
+                Some(c) => c,
+            };
+            if prev.map(|start| start <= '/' && '/' <= end).unwrap_or(false) {
+                slash = true;
+            }
+            prev = None;
+            continue
+        }
+        if c == '/' {
+            slash = true;
+        }
+        prev = Some(c);
+    }
+    slash != negated
+}
+
+#[derive(Clone)]
+enum Segment {
+    /// Static text
+    Static(String),
+    /// Whole segment parameter, `{name}`
+    Param(String),
+    /// Static text mixed with parameters, `{name}.{ext}`
+    Regex(SegmentRegex),
+    /// Parameter with custom regex, matches rest of the path
+    Tail(SegmentRegex),
+}
+
+impl Segment {
+    /// Segments with same key match same text
+    fn key(&self) -> String {
+        match *self {
+            Segment::Static(ref text) => format!("s{}", text),
+            Segment::Param(_) => "p".to_owned(),
+            Segment::Regex(ref re) => format!("r{}", re.key),
+            Segment::Tail(ref re) => format!("t{}", re.key),
+        }
+    }
+}
+
+/// Prefix tree of path segments.
+///
+/// Static segments beat dynamic ones. Segments are tried in order: static text,
+/// mixed segments, parameters and custom regex patterns, same kind of segments
+/// are tried in registration order.
+#[derive(Default)]
+struct Node {
+    resource: Option<usize>,
+    statics: HashMap<String, Node>,
+    regex: Vec<(SegmentRegex, Node)>,
+    params: Vec<(String, Node)>,
+    tails: Vec<(SegmentRegex, usize)>,
+}
+
+impl Node {
+    fn insert(&mut self, segments: &[Segment], idx: usize) {
+        let (segment, rest) = if let Some(item) = segments.split_first() {
+            item
+        } else {
+            if self.resource.is_none() {
+                self.resource = Some(idx);
+            }
+            return
+        };
+
+        let node = match *segment {
+            Segment::Static(ref text) =>
+                self.statics.entry(text.clone()).or_insert_with(Node::default),
+            Segment::Param(ref name) => {
+                let pos = self.params.iter().position(|&(ref n, _)| n == name);
+                if let Some(pos) = pos {
+                    &mut self.params[pos].1
+                } else {
+                    self.params.push((name.clone(), Node::default()));
+                    &mut self.params.last_mut().unwrap().1
+                }
+            }
+            Segment::Regex(ref re) => {
+                let pos = self.regex.iter().position(
+                    |&(ref r, _)| r.re.as_str() == re.re.as_str());
+                if let Some(pos) = pos {
+                    &mut self.regex[pos].1
+                } else {
+                    self.regex.push((re.clone(), Node::default()));
+                    &mut self.regex.last_mut().unwrap().1
+                }
+            }
+            Segment::Tail(ref re) => {
+                self.tails.push((re.clone(), idx));
+                return
+            }
+        };
+        node.insert(rest, idx)
+    }
+
+    /// Find resource for path, path has to start with slash
+    fn recognize<'a>(&'a self, path: &'a str, params: &mut Vec<(&'a str, &'a str)>)
+                     -> Option<usize>
+    {
+        if path.is_empty() {
+            self.find("", params)
+        } else if path.starts_with('/') {
+            self.find(&path[1..], params)
+        } else {
+            None
+        }
+    }
+
+    /// Match path starting from current segment
+    fn find<'a>(&'a self, path: &'a str, params: &mut Vec<(&'a str, &'a str)>)
+                -> Option<usize>
+    {
+        let (segment, rest) = match path.find('/') {
+            Some(pos) => (&path[..pos], Some(&path[pos+1..])),
+            None => (path, None),
+        };
+        let len = params.len();
+
+        if let Some(node) = self.statics.get(segment) {
+            if let Some(idx) = node.next(rest, params) {
+                return Some(idx)
+            }
+        }
+        for &(ref re, ref node) in &self.regex {
+            if re.matches(segment, params) {
+                if let Some(idx) = node.next(rest, params) {
+                    return Some(idx)
+                }
+                params.truncate(len);
+            }
+        }
+        if !segment.is_empty() {
+            for &(ref name, ref node) in &self.params {
+                params.push((name.as_str(), segment));
+                if let Some(idx) = node.next(rest, params) {
+                    return Some(idx)
+                }
+                params.truncate(len);
+            }
+        }
+        for &(ref re, idx) in &self.tails {
+            if re.matches(path, params) {
+                return Some(idx)
+            }
+        }
+        None
+    }
+
+    fn next<'a>(&'a self, rest: Option<&'a str>, params: &mut Vec<(&'a str, &'a str)>)
+                -> Option<usize>
+    {
+        match rest {
+            Some(path) => self.find(path, params),
+            None => self.resource,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum PatternElement {
     Str(String),
@@ -148,11 +437,11 @@ enum PatternElement {
 
 #[derive(Clone)]
 pub struct Pattern {
-    re: Regex,
     name: String,
+    path: String,
     pattern: String,
-    names: Vec<String>,
     elements: Vec<PatternElement>,
+    segments: Vec<Segment>,
 }
 
 impl Pattern {
@@ -160,23 +449,25 @@ impl Pattern {
     ///
     /// Panics if path pattern is wrong.
     pub fn new(name: &str, path: &str) -> Self {
-        let (pattern, elements) = Pattern::parse(path);
+        match Pattern::try_new(name, path) {
+            Ok(pattern) => pattern,
+            Err(err) => panic!("{}", err),
+        }
+    }
 
-        let re = match Regex::new(&pattern) {
-            Ok(re) => re,
-            Err(err) => panic!("Wrong path pattern: \"{}\" {}", path, err)
-        };
-        let names = re.capture_names()
-            .filter_map(|name| name.map(|name| name.to_owned()))
-            .collect();
+    /// Parse path pattern, returns error if path pattern is wrong.
+    pub fn try_new(name: &str, path: &str) -> Result<Self, RouterError> {
+        let (pattern, elements) = Pattern::parse(path);
+        let segments = Pattern::segments(path)
+            .map_err(|err| RouterError::Pattern(path.to_owned(), format!("{}", err)))?;
 
-        Pattern {
-            re: re,
+        Ok(Pattern {
             name: name.into(),
+            path: path.to_owned(),
             pattern: pattern,
-            names: names,
             elements: elements,
-        }
+            segments: segments,
+        })
     }
 
     /// Returns name of the pattern
@@ -189,24 +480,6 @@ impl Pattern {
         &self.pattern
     }
 
-    /// Extract pattern parameters from the text
-    pub(crate) fn update_match_info<S>(&self, text: &str, req: &mut HttpRequest<S>) {
-        if !self.names.is_empty() {
-            if let Some(captures) = self.re.captures(text) {
-                let mut idx = 0;
-                for capture in captures.iter() {
-                    if let Some(ref m) = capture {
-                        if idx != 0 {
-                            req.match_info_mut().add(
-                                self.names[idx-1].as_str(), m.as_str());
-                        }
-                        idx += 1;
-                    }
-                }
-            };
-        }
-    }
-
     /// Build pattern path.
     pub fn path<U, I>(&self, prefix: Option<&str>, elements: U)
                       -> Result<String, UrlGenerationError>
@@ -236,9 +509,77 @@ impl Pattern {
     }
 
     fn parse(pattern: &str) -> (String, Vec<PatternElement>) {
+        // All routes must have a leading slash so its optional to have one
+        let pattern = if pattern.starts_with('/') { &pattern[1..] } else { pattern };
+        let (re, elems) = Pattern::parse_fragment(pattern);
+        (format!("^/{}$", re), elems)
+    }
+
+    /// Split pattern into tree segments
+    fn segments(path: &str) -> Result<Vec<Segment>, regex::Error> {
+        let path = if path.starts_with('/') { &path[1..] } else { path };
+        let parts = Pattern::split(path);
+
+        let mut segments = Vec::new();
+        for (idx, part) in parts.iter().enumerate() {
+            if !part.contains('{') {
+                segments.push(Segment::Static((*part).to_owned()));
+            } else if Pattern::has_custom_regex(part) {
+                // custom regex can match slashes, it has to match rest of the path
+                let (re, _) = Pattern::parse_fragment(&parts[idx..].join("/"));
+                segments.push(Segment::Tail(SegmentRegex::new(&re)?));
+                break
+            } else if part.starts_with('{') && part.ends_with('}') &&
+                part.matches('{').count() == 1
+            {
+                segments.push(Segment::Param(part[1..part.len()-1].to_owned()));
+            } else {
+                let (re, _) = Pattern::parse_fragment(part);
+                segments.push(Segment::Regex(SegmentRegex::new(&re)?));
+            }
+        }
+        Ok(segments)
+    }
+
+    /// Split path by slashes, slashes inside of `{}` do not split
+    fn split(path: &str) -> Vec<&str> {
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut in_param = false;
+        for (idx, ch) in path.char_indices() {
+            match ch {
+                '{' => in_param = true,
+                '}' => in_param = false,
+                '/' if !in_param => {
+                    parts.push(&path[start..idx]);
+                    start = idx + 1;
+                }
+                _ => (),
+            }
+        }
+        parts.push(&path[start..]);
+        parts
+    }
+
+    /// Check if segment contains parameter with custom regex, `{name:regex}`
+    fn has_custom_regex(part: &str) -> bool {
+        let mut in_param = false;
+        for ch in part.chars() {
+            match ch {
+                '{' => in_param = true,
+                '}' => in_param = false,
+                ':' if in_param => return true,
+                _ => (),
+            }
+        }
+        false
+    }
+
+    /// Convert pattern to regex, pattern does not include leading slash
+    fn parse_fragment(pattern: &str) -> (String, Vec<PatternElement>) {
         const DEFAULT_PATTERN: &str = "[^/]+";
 
-        let mut re = String::from("^/");
+        let mut re = String::new();
         let mut el = String::new();
         let mut in_param = false;
         let mut in_param_pattern = false;
@@ -246,12 +587,7 @@ impl Pattern {
         let mut param_pattern = String::from(DEFAULT_PATTERN);
         let mut elems = Vec::new();
 
-        for (index, ch) in pattern.chars().enumerate() {
-            // All routes must have a leading slash so its optional to have one
-            if index == 0 && ch == '/' {
-                continue;
-            }
-
+        for ch in pattern.chars() {
             if in_param {
                 // In parameter segment: `{....}`
                 if ch == '}' {
@@ -285,7 +621,6 @@ impl Pattern {
             }
         }
 
-        re.push('$');
         (re, elems)
     }
 }
@@ -312,14 +647,14 @@ mod tests {
 
     #[test]
     fn test_recognizer() {
-        let mut routes = HashMap::new();
-        routes.insert(Pattern::new("", "/name"), Some(Resource::default()));
-        routes.insert(Pattern::new("", "/name/{val}"), Some(Resource::default()));
-        routes.insert(Pattern::new("", "/name/{val}/index.html"), Some(Resource::default()));
-        routes.insert(Pattern::new("", "/v{val}/{val2}/index.html"), Some(Resource::default()));
-        routes.insert(Pattern::new("", "/v/{tail:.*}"), Some(Resource::default()));
-        routes.insert(Pattern::new("", "{test}/index.html"), Some(Resource::default()));
-        let (rec, _) = Router::new::<()>("", ServerSettings::default(), routes);
+        let mut routes = Vec::new();
+        routes.push((Pattern::new("", "/name"), Some(Resource::default())));
+        routes.push((Pattern::new("", "/name/{val}"), Some(Resource::default())));
+        routes.push((Pattern::new("", "/name/{val}/index.html"), Some(Resource::default())));
+        routes.push((Pattern::new("", "/v{val}/{val2}/index.html"), Some(Resource::default())));
+        routes.push((Pattern::new("", "/v/{tail:.*}"), Some(Resource::default())));
+        routes.push((Pattern::new("", "{test}/index.html"), Some(Resource::default())));
+        let (rec, _) = Router::new::<()>("", ServerSettings::default(), routes).unwrap();
 
         let mut req = TestRequest::with_uri("/name").finish();
         assert!(rec.recognize(&mut req).is_some());
@@ -350,10 +685,10 @@ mod tests {
 
     #[test]
     fn test_recognizer_with_prefix() {
-        let mut routes = HashMap::new();
-        routes.insert(Pattern::new("", "/name"), Some(Resource::default()));
-        routes.insert(Pattern::new("", "/name/{val}"), Some(Resource::default()));
-        let (rec, _) = Router::new::<()>("/test", ServerSettings::default(), routes);
+        let mut routes = Vec::new();
+        routes.push((Pattern::new("", "/name"), Some(Resource::default())));
+        routes.push((Pattern::new("", "/name/{val}"), Some(Resource::default())));
+        let (rec, _) = Router::new::<()>("/test", ServerSettings::default(), routes).unwrap();
 
         let mut req = TestRequest::with_uri("/name").finish();
         assert!(rec.recognize(&mut req).is_none());
@@ -367,10 +702,10 @@ mod tests {
         assert_eq!(&req.match_info()["val"], "value");
 
         // same patterns
-        let mut routes = HashMap::new();
-        routes.insert(Pattern::new("", "/name"), Some(Resource::default()));
-        routes.insert(Pattern::new("", "/name/{val}"), Some(Resource::default()));
-        let (rec, _) = Router::new::<()>("/test2", ServerSettings::default(), routes);
+        let mut routes = Vec::new();
+        routes.push((Pattern::new("", "/name"), Some(Resource::default())));
+        routes.push((Pattern::new("", "/name/{val}"), Some(Resource::default())));
+        let (rec, _) = Router::new::<()>("/test2", ServerSettings::default(), routes).unwrap();
 
         let mut req = TestRequest::with_uri("/name").finish();
         assert!(rec.recognize(&mut req).is_none());
@@ -378,6 +713,104 @@ mod tests {
         assert!(rec.recognize(&mut req).is_some());
     }
 
+    #[test]
+    fn test_recognizer_priority() {
+        let mut routes = Vec::new();
+        let mut resource = Resource::default();
+        resource.name("static");
+        routes.push((Pattern::new("", "/name/new"), Some(resource)));
+        let mut resource = Resource::default();
+        resource.name("param");
+        routes.push((Pattern::new("", "/name/{val}"), Some(resource)));
+        let mut resource = Resource::default();
+        resource.name("ext");
+        routes.push((Pattern::new("", "/name/{val}-{ext}"), Some(resource)));
+        let mut resource = Resource::default();
+        resource.name("tail");
+        routes.push((Pattern::new("", "/{tail:.*}"), Some(resource)));
+        let (rec, resources) = Router::new::<()>("", ServerSettings::default(), routes).unwrap();
+
+        let mut req = TestRequest::with_uri("/name/new").finish();
+        let idx = rec.recognize(&mut req).unwrap();
+        assert_eq!(resources[idx].get_name(), "static");
+        assert!(req.match_info().is_empty());
+
+        let mut req = TestRequest::with_uri("/name/value").finish();
+        let idx = rec.recognize(&mut req).unwrap();
+        assert_eq!(resources[idx].get_name(), "param");
+        assert_eq!(req.match_info().get("val").unwrap(), "value");
+
+        let mut req = TestRequest::with_uri("/name/index-html").finish();
+        let idx = rec.recognize(&mut req).unwrap();
+        assert_eq!(resources[idx].get_name(), "ext");
+        assert_eq!(req.match_info().get("val").unwrap(), "index");
+        assert_eq!(req.match_info().get("ext").unwrap(), "html");
+
+        let mut req = TestRequest::with_uri("/name/new/other").finish();
+        let idx = rec.recognize(&mut req).unwrap();
+        assert_eq!(resources[idx].get_name(), "tail");
+        assert_eq!(req.match_info().get("tail").unwrap(), "name/new/other");
+    }
+
+    #[test]
+    fn test_router_conflicts() {
+        let routes = vec![
+            (Pattern::new("", "/user/{id}"), Some(Resource::<()>::default())),
+            (Pattern::new("", "/user/{name}"), Some(Resource::default()))];
+        let err = Router::new("", ServerSettings::default(), routes).err().unwrap();
+        assert_eq!(err, RouterError::Duplicate(
+            "/user/{id}".to_owned(), "/user/{name}".to_owned()));
+
+        let routes = vec![
+            (Pattern::new("", "/static/{tail:.*}"), Some(Resource::<()>::default())),
+            (Pattern::new("", "/static/{id:\\d+}"), Some(Resource::default()))];
+        let err = Router::new("", ServerSettings::default(), routes).err().unwrap();
+        assert_eq!(format!("{}", err),
+                   "Resource \"/static/{id:\\\\d+}\" is shadowed by \"/static/{tail:.*}\"");
+
+        let routes = vec![
+            (Pattern::new("user", "/user/{id}"), Some(Resource::<()>::default())),
+            (Pattern::new("user", "/users/{id}"), Some(Resource::default()))];
+        let err = Router::new("", ServerSettings::default(), routes).err().unwrap();
+        assert_eq!(err, RouterError::Duplicate(
+            "/user/{id}".to_owned(), "/users/{id}".to_owned()));
+
+        // parameter segment is matched before custom regex
+        let routes = vec![
+            (Pattern::new("", "/user/{id}"), Some(Resource::<()>::default())),
+            (Pattern::new("", "/user/{id:\\d+}"), Some(Resource::default()))];
+        let err = Router::new("", ServerSettings::default(), routes).err().unwrap();
+        assert_eq!(err, RouterError::Shadowed(
+            "/user/{id}".to_owned(), "/user/{id:\\d+}".to_owned()));
+
+        let routes = vec![
+            (Pattern::new("", "/user/{id:[^/]+}"), Some(Resource::<()>::default())),
+            (Pattern::new("", "/user/{name}"), Some(Resource::default()))];
+        let err = Router::new("", ServerSettings::default(), routes).err().unwrap();
+        assert_eq!(err, RouterError::Shadowed(
+            "/user/{name}".to_owned(), "/user/{id:[^/]+}".to_owned()));
+
+        // regex matches paths with more segments
+        let routes = vec![
+            (Pattern::new("", "/files/{name}"), Some(Resource::<()>::default())),
+            (Pattern::new("", "/files/{path:.+}"), Some(Resource::default())),
+            (Pattern::new("", "/files/{path:[a-z/]+}.txt"), Some(Resource::default())),
+            (Pattern::new("", "/user/{name}"), Some(Resource::default())),
+            (Pattern::new("", "/user/{id:\\d*}"), Some(Resource::default()))];
+        assert!(Router::new("", ServerSettings::default(), routes).is_ok());
+
+        // static segments are matched before custom regex
+        let routes = vec![
+            (Pattern::new("", "/static/{tail:.*}"), Some(Resource::<()>::default())),
+            (Pattern::new("", "/static/index.html"), Some(Resource::default()))];
+        assert!(Router::new("", ServerSettings::default(), routes).is_ok());
+
+        match Pattern::try_new("", "/user/{id:[}") {
+            Err(RouterError::Pattern(path, _)) => assert_eq!(path, "/user/{id:[}"),
+            _ => panic!("pattern error is expected"),
+        }
+    }
+
     fn assert_parse(pattern: &str, expected_re: &str) -> Regex {
         let (re_str, _) = Pattern::parse(pattern);
         assert_eq!(&*re_str, expected_re);
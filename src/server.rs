@@ -1,19 +1,45 @@
-use std::{io, net, thread};
+use std::{fmt, io, net, thread};
 use std::rc::Rc;
 use std::sync::{Arc, mpsc as sync_mpsc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
 use std::time::Duration;
 use std::marker::PhantomData;
-use std::collections::HashMap;
+
+#[cfg(unix)]
+use std::{env, fs, mem, process};
+#[cfg(unix)]
+use std::ffi::OsString;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::os::unix::net as unix;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+#[cfg(unix)]
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
 
 use actix::dev::*;
 use actix::System;
 use futures::{Future, Sink, Stream};
 use futures::sync::mpsc;
+#[cfg(unix)]
+use futures::sync::oneshot;
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio_core::net::TcpStream;
-use mio;
+use mio::{self, Evented};
 use num_cpus;
 use net2::TcpBuilder;
+#[cfg(unix)]
+use net2::unix::UnixTcpBuilderExt;
+
+#[cfg(unix)]
+use libc;
+#[cfg(unix)]
+use mio_uds;
 
 #[cfg(feature="tls")]
 use native_tls::TlsAcceptor;
@@ -21,9 +47,25 @@ use native_tls::TlsAcceptor;
 use tokio_tls::TlsStream;
 
 #[cfg(feature="alpn")]
-use openssl::ssl::{SslMethod, SslAcceptorBuilder};
+use std::sync::RwLock;
+#[cfg(feature="alpn")]
+use std::collections::HashMap;
+#[cfg(feature="alpn")]
+use openssl::ssl::{SslMethod, SslAcceptor, SslAcceptorBuilder, SslContext, SslContextBuilder,
+                   SSL_VERIFY_PEER, SSL_VERIFY_FAIL_IF_NO_PEER_CERT,
+                   SSL_OP_NO_SSLV2, SSL_OP_NO_SSLV3, SSL_OP_NO_COMPRESSION, SSL_OP_NO_TICKET,
+                   SSL_OP_SINGLE_DH_USE, SSL_OP_SINGLE_ECDH_USE,
+                   SSL_OP_CIPHER_SERVER_PREFERENCE};
+#[cfg(feature="alpn")]
+use openssl::dh::Dh;
 #[cfg(feature="alpn")]
-use openssl::pkcs12::ParsedPkcs12;
+use openssl::x509::{X509, X509Ref, X509Name};
+#[cfg(feature="alpn")]
+use openssl::pkey::{PKey, PKeyRef};
+#[cfg(feature="alpn")]
+use openssl::error::ErrorStack;
+#[cfg(feature="alpn")]
+use openssl::pkcs12::{Pkcs12, ParsedPkcs12};
 #[cfg(feature="alpn")]
 use tokio_openssl::SslStream;
 
@@ -31,8 +73,17 @@ use tokio_openssl::SslStream;
 use actix::actors::signal;
 
 use helpers;
-use channel::{HttpChannel, HttpHandler, IntoHttpHandler};
-use worker::{Conn, Worker, WorkerSettings, StreamHandlerType, StopWorker};
+use fs::StaticFiles;
+use httpcodes;
+use httprequest::HttpRequest;
+use httpresponse::HttpResponse;
+use application::{Application, HttpApplication};
+use http::{header, Error as HttpError};
+use channel::{HttpChannel, HttpHandler, IntoHttpHandler, Connection, ConnectHandler};
+use h1::Http1Limits;
+use h2::Http2Settings;
+use worker::{Conn, StdStream, Worker, WorkerSettings, StreamHandlerType,
+             StopWorker, PauseWorker, ResumeWorker};
 
 /// Various server settings
 #[derive(Debug, Clone)]
@@ -85,6 +136,44 @@ impl ServerSettings {
     }
 }
 
+/// Tls client certificate verification
+///
+/// Verified certificate is available with
+/// [`ConnectionInfo::peer_certificate()`](./dev/struct.ConnectionInfo.html#method.peer_certificate).
+#[cfg(feature="alpn")]
+#[derive(Debug, Clone)]
+pub struct ClientAuth {
+    ca_file: ::std::path::PathBuf,
+    required: bool,
+}
+
+#[cfg(feature="alpn")]
+impl ClientAuth {
+    /// Request client certificate and verify it with CA certificates
+    /// from PEM file. Clients without certificate are accepted.
+    pub fn optional<P: AsRef<::std::path::Path>>(ca_file: P) -> ClientAuth {
+        ClientAuth { ca_file: ca_file.as_ref().to_owned(), required: false }
+    }
+
+    /// Reject connections without valid client certificate
+    pub fn required<P: AsRef<::std::path::Path>>(ca_file: P) -> ClientAuth {
+        ClientAuth { ca_file: ca_file.as_ref().to_owned(), required: true }
+    }
+
+    fn configure(&self, builder: &mut SslContextBuilder) -> Result<(), ErrorStack> {
+        builder.set_ca_file(&self.ca_file)?;
+        builder.set_client_ca_list(X509Name::load_client_ca_file(&self.ca_file)?);
+        if self.required {
+            builder.set_verify(SSL_VERIFY_PEER | SSL_VERIFY_FAIL_IF_NO_PEER_CERT);
+        } else {
+            builder.set_verify(SSL_VERIFY_PEER);
+        }
+        // openssl does not resume sessions of verified peers without context id
+        builder.set_session_id_context(b"actix-web")?;
+        Ok(())
+    }
+}
+
 /// An HTTP Server
 ///
 /// `T` - async stream, anything that implements `AsyncRead` + `AsyncWrite`.
@@ -100,14 +189,38 @@ pub struct HttpServer<T, A, H, U>
     addr: PhantomData<A>,
     threads: usize,
     backlog: i32,
+    dispatch: Dispatch,
+    maxconn: Option<usize>,
+    reuse_port: bool,
+    proxy_protocol: bool,
+    limits: Http1Limits,
+    http2: Http2Settings,
+    client_timeout: u64,
+    min_body_rate: usize,
+    write_timeout: u64,
+    max_requests: usize,
+    on_connect: Option<ConnectHandler>,
+    #[cfg(feature="alpn")]
+    client_auth: Option<ClientAuth>,
+    #[cfg(feature="alpn")]
+    ssl: Option<Arc<RwLock<SslAcceptor>>>,
+    #[cfg(feature="alpn")]
+    sni: Arc<HashMap<String, SslContext>>,
     host: Option<String>,
     keep_alive: Option<u64>,
+    acme_dir: Option<::std::path::PathBuf>,
     factory: Arc<Fn() -> U + Send + Sync>,
-    workers: Vec<SyncAddress<Worker<H>>>,
-    sockets: HashMap<net::SocketAddr, net::TcpListener>,
+    /// factories of listeners bound with `bind_with()`
+    factories: Vec<Arc<Fn(ServerSettings) -> Vec<Box<HttpHandler>> + Send + Sync>>,
+    workers: Vec<SyncAddress<Worker<Box<HttpHandler>>>>,
+    sockets: Vec<Socket>,
     accept: Vec<(mio::SetReadiness, sync_mpsc::Sender<Command>)>,
     exit: bool,
     shutdown_timeout: u16,
+    #[cfg(unix)]
+    uds_mode: Option<u32>,
+    #[cfg(unix)]
+    ready_fd: Option<RawFd>,
 }
 
 unsafe impl<T, A, H, U> Sync for HttpServer<T, A, H, U> where H: 'static {}
@@ -145,14 +258,37 @@ impl<T, A, H, U, V> HttpServer<T, A, H, U>
                     addr: PhantomData,
                     threads: num_cpus::get(),
                     backlog: 2048,
+                    dispatch: Dispatch::RoundRobin,
+                    maxconn: None,
+                    reuse_port: false,
+                    proxy_protocol: false,
+                    limits: Http1Limits::default(),
+                    http2: Http2Settings::default(),
+                    client_timeout: 0,
+                    min_body_rate: 0,
+                    write_timeout: 0,
+                    max_requests: 0,
+                    on_connect: None,
+                    #[cfg(feature="alpn")]
+                    client_auth: None,
+                    #[cfg(feature="alpn")]
+                    ssl: None,
+                    #[cfg(feature="alpn")]
+                    sni: Arc::new(HashMap::new()),
                     host: None,
                     keep_alive: None,
+                    acme_dir: None,
                     factory: Arc::new(factory),
+                    factories: Vec::new(),
                     workers: Vec::new(),
-                    sockets: HashMap::new(),
+                    sockets: Vec::new(),
                     accept: Vec::new(),
                     exit: false,
                     shutdown_timeout: 30,
+                    #[cfg(unix)]
+                    uds_mode: None,
+                    #[cfg(unix)]
+                    ready_fd: None,
         }
     }
 
@@ -178,6 +314,158 @@ impl<T, A, H, U, V> HttpServer<T, A, H, U>
         self
     }
 
+    /// Set strategy of distributing accepted connections between workers.
+    ///
+    /// By default connections are distributed round-robin. Use
+    /// `Dispatch::LeastConnections` if server handles long-lived
+    /// connections, like websockets.
+    pub fn dispatch(mut self, dispatch: Dispatch) -> Self {
+        self.dispatch = dispatch;
+        self
+    }
+
+    /// Set maximum number of concurrent connections per worker.
+    ///
+    /// When all workers reach this limit, server stops accepting new
+    /// connections. Pending connections wait in the listen backlog.
+    /// Accepting resumes once number of connections of some worker drops
+    /// by 10 percent. By default number of connections is not limited.
+    pub fn max_connections(mut self, num: usize) -> Self {
+        self.maxconn = if num > 0 { Some(num) } else { None };
+        self
+    }
+
+    /// Accept connections in workers on separate `SO_REUSEPORT` listeners.
+    ///
+    /// Each worker binds its own listener for every tcp address and accepts
+    /// connections without dedicated accept thread, kernel distributes
+    /// connections between workers. Unix domain sockets and adopted
+    /// listeners are still served by accept threads.
+    ///
+    /// This method should be called before `bind()` method call, sockets
+    /// bound earlier are served by accept threads.
+    #[cfg(unix)]
+    pub fn reuse_port(mut self, enable: bool) -> Self {
+        self.reuse_port = enable;
+        self
+    }
+
+    /// Expect PROXY protocol header on accepted connections.
+    ///
+    /// Both text (v1) and binary (v2) headers are supported. Client address
+    /// advertised by proxy is used as request peer address. Connections
+    /// without valid header get closed. Enable it only if all connections
+    /// come through proxy. Header has to be received within client timeout,
+    /// or 5 seconds if client timeout is disabled.
+    pub fn proxy_protocol(mut self, enable: bool) -> Self {
+        self.proxy_protocol = enable;
+        self
+    }
+
+    /// Set http/1 request parser limits.
+    ///
+    /// Check [`Http1Limits`](./dev/struct.Http1Limits.html) documentation
+    /// for default values.
+    pub fn limits(mut self, limits: Http1Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set http/2 connection settings.
+    ///
+    /// Check [`Http2Settings`](./dev/struct.Http2Settings.html) documentation
+    /// for available settings.
+    pub fn h2_settings(mut self, settings: Http2Settings) -> Self {
+        self.http2 = settings;
+        self
+    }
+
+    /// Set request head read timeout in milliseconds.
+    ///
+    /// Timeout covers time from connection accept, or from first byte of next
+    /// request on keep-alive connection, until request head is received.
+    /// On timeout server responds with `408 Request Timeout` and closes connection.
+    /// To disable timeout set value to 0. By default timeout is disabled.
+    pub fn client_timeout(mut self, val: u64) -> Self {
+        self.client_timeout = val;
+        self
+    }
+
+    /// Set minimum request payload receive rate in bytes per second.
+    ///
+    /// Rate is checked every 5 seconds while payload is received. Slow payload
+    /// gets `PayloadError::Timeout` error and connection closes after response.
+    /// By default rate is not checked.
+    pub fn min_body_rate(mut self, val: usize) -> Self {
+        self.min_body_rate = val;
+        self
+    }
+
+    /// Set response write timeout in seconds.
+    ///
+    /// If client does not read response and no data can be sent to it within
+    /// timeout, http/1 connection gets closed and http/2 stream gets reset.
+    /// Request handler gets notified about disconnect. By default timeout is disabled.
+    pub fn write_timeout(mut self, val: u64) -> Self {
+        self.write_timeout = val;
+        self
+    }
+
+    /// Set maximum number of requests served over one connection.
+    ///
+    /// Last http/1 response gets `Connection: close` header, http/2 connection
+    /// receives GOAWAY frame, so client has to re-connect. By default there is no limit.
+    pub fn max_requests_per_connection(mut self, num: usize) -> Self {
+        self.max_requests = num;
+        self
+    }
+
+    /// Set callback for accepted connections.
+    ///
+    /// Callback runs in worker thread before any request is read, after tls
+    /// handshake and PROXY protocol header. Returning `false` closes connection.
+    /// Tls client certificate and server name are available with
+    /// `Connection::peer_certificate()` and `Connection::server_name()`.
+    /// Data attached with `Connection::insert()` is available in extensions
+    /// of every request of the connection.
+    pub fn on_connect<F>(mut self, f: F) -> Self
+        where F: Fn(&mut Connection) -> bool + Send + Sync + 'static
+    {
+        self.on_connect = Some(Arc::new(f));
+        self
+    }
+
+    /// Request tls client certificates.
+    ///
+    /// Applies to `start_ssl()` only, `start_tls()` returns error
+    /// if client authentication is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is called after `ssl_identity()`.
+    #[cfg(feature="alpn")]
+    pub fn client_auth(mut self, auth: ClientAuth) -> Self {
+        assert!(self.sni.is_empty(), "client_auth() has to be called before ssl_identity()");
+        self.client_auth = Some(auth);
+        self
+    }
+
+    /// Add tls certificate for specific host name.
+    ///
+    /// Certificate is selected by server name (SNI) sent by client during
+    /// handshake. Certificate passed to `start_ssl()` is used if client does not
+    /// send server name or no certificate matches it. Negotiated server name
+    /// is available with `ConnectionInfo::server_name()`.
+    #[cfg(feature="alpn")]
+    pub fn ssl_identity<S: AsRef<str>>(mut self, host: S, identity: &ParsedPkcs12)
+                                       -> io::Result<Self>
+    {
+        let ctx = sni_context(identity, self.client_auth.as_ref())
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        Arc::make_mut(&mut self.sni).insert(host.as_ref().to_lowercase(), ctx);
+        Ok(self)
+    }
+
     /// Set server keep-alive setting.
     ///
     /// By default keep alive is enabled.
@@ -223,22 +511,106 @@ impl<T, A, H, U, V> HttpServer<T, A, H, U>
         self
     }
 
+    /// Set permissions of unix domain socket files.
+    ///
+    /// By default socket file permissions are defined by process umask.
+    /// Socket file never exists with permissions looser than `mode`.
+    ///
+    /// This method should be called before `bind_uds()` method call.
+    #[cfg(unix)]
+    pub fn uds_mode(mut self, mode: u32) -> Self {
+        self.uds_mode = Some(mode);
+        self
+    }
+
     /// Get addresses of bound sockets.
+    ///
+    /// Unix domain sockets are not included.
     pub fn addrs(&self) -> Vec<net::SocketAddr> {
-        self.sockets.keys().cloned().collect()
+        self.sockets.iter().filter_map(|s| s.addr.tcp()).collect()
     }
 
     /// The socket address to bind
     ///
     /// To mind multiple addresses this method can be call multiple times.
-    pub fn bind<S: net::ToSocketAddrs>(mut self, addr: S) -> io::Result<Self> {
+    pub fn bind<S: net::ToSocketAddrs>(self, addr: S) -> io::Result<Self> {
+        self.bind_group(addr, 0)
+    }
+
+    /// The socket address to bind with its own application factory.
+    ///
+    /// Connections accepted on this address are handled by applications
+    /// created by `factory` instead of server's application factory.
+    /// Applications may use different state type.
+    /// All listeners share workers, so pause, resume and stop messages
+    /// apply to all of them.
+    ///
+    /// ```rust,ignore
+    /// HttpServer::new(|| vec![Application::with_state(State).resource("/", |r| r.h(index))])
+    ///     .bind("127.0.0.1:8080")?
+    ///     .bind_with("127.0.0.1:8081", || vec![Application::new().prefix("/admin")])?
+    ///     .start();
+    /// ```
+    pub fn bind_with<S, F, U2, V2>(mut self, addr: S, factory: F) -> io::Result<Self>
+        where S: net::ToSocketAddrs,
+              F: Sync + Send + 'static + Fn() -> U2,
+              U2: IntoIterator<Item=V2>,
+              V2: IntoHttpHandler,
+    {
+        self.factories.push(Arc::new(move |settings: ServerSettings| {
+            factory().into_iter()
+                .map(|app| Box::new(app.into_handler(settings.clone())) as Box<HttpHandler>)
+                .collect()
+        }));
+        let group = self.factories.len();
+        self.bind_group(addr, group)
+    }
+
+    /// The socket address of plain http listener that redirects to https.
+    ///
+    /// Every request gets `301 Moved Permanently` response with the same url
+    /// and `https` scheme. Url host is taken from
+    /// [`ConnectionInfo::host()`](./dev/struct.ConnectionInfo.html#method.host),
+    /// port is removed, so https has to use default port.
+    ///
+    /// ```rust,ignore
+    /// HttpServer::new(|| Application::new().resource("/", |r| r.h(index)))
+    ///     .bind("0.0.0.0:443")?
+    ///     .redirect_http_to_https("0.0.0.0:80")?
+    ///     .start_ssl(&identity)?;
+    /// ```
+    pub fn redirect_http_to_https<S: net::ToSocketAddrs>(self, addr: S) -> io::Result<Self> {
+        let num = self.sockets.len();
+        let mut srv = self.bind_group(addr, 0)?;
+        for sock in &mut srv.sockets[num..] {
+            sock.redirect = true;
+        }
+        Ok(srv)
+    }
+
+    /// Serve ACME HTTP-01 challenge files on redirecting listeners.
+    ///
+    /// Requests to `/.well-known/acme-challenge/{token}` are served
+    /// from `dir` instead of redirecting them to https.
+    pub fn acme_challenge<P: Into<::std::path::PathBuf>>(mut self, dir: P) -> Self {
+        self.acme_dir = Some(dir.into());
+        self
+    }
+
+    fn bind_group<S: net::ToSocketAddrs>(mut self, addr: S, group: usize) -> io::Result<Self> {
         let mut err = None;
         let mut succ = false;
         for addr in addr.to_socket_addrs()? {
-            match create_tcp_listener(addr, self.backlog) {
+            match create_tcp_listener(addr, self.backlog, self.reuse_port) {
                 Ok(lst) => {
                     succ = true;
-                    self.sockets.insert(lst.local_addr().unwrap(), lst);
+                    self.sockets.push(Socket{
+                        addr: ListenAddr::Tcp(lst.local_addr().unwrap()),
+                        lst: Listener::Tcp(lst),
+                        adopted: false,
+                        group: group,
+                        redirect: false,
+                        reuse_port: self.reuse_port});
                 },
                 Err(e) => err = Some(e),
             }
@@ -255,26 +627,258 @@ impl<T, A, H, U, V> HttpServer<T, A, H, U>
         }
     }
 
-    fn start_workers(&mut self, settings: &ServerSettings, handler: &StreamHandlerType)
-                     -> Vec<mpsc::UnboundedSender<Conn<net::TcpStream>>>
+    /// The unix domain socket path to bind
+    ///
+    /// If socket file already exists and no process accepts connections on it,
+    /// stale file get removed. Socket file get removed on server stop.
+    /// This method can be called multiple times.
+    #[cfg(unix)]
+    pub fn bind_uds<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        let addr = ListenAddr::Unix(path.as_ref().to_owned(), self.uds_mode);
+        let lst = addr.listen(self.backlog)?;
+        self.sockets.push(
+            Socket{addr: addr, lst: lst, adopted: false, group: 0,
+                   redirect: false, reuse_port: false});
+        Ok(self)
+    }
+
+    /// Use already opened tcp listener.
+    ///
+    /// Listener has to be bound and listening. Server does not re-create adopted
+    /// sockets, `PauseServer` stops accepting connections but keeps socket open,
+    /// so pending connections wait in socket backlog until `ResumeServer`.
+    pub fn listen(mut self, lst: net::TcpListener) -> Self {
+        let addr = lst.local_addr().expect("Can not get listener address");
+        self.sockets.push(
+            Socket{addr: ListenAddr::Tcp(addr), lst: Listener::Tcp(lst),
+                   adopted: true, group: 0, redirect: false, reuse_port: false});
+        self
+    }
+
+    /// Use already opened unix domain socket listener.
+    ///
+    /// Socket file is not removed on server stop.
+    #[cfg(unix)]
+    pub fn listen_uds(mut self, lst: unix::UnixListener) -> Self {
+        let path = lst.local_addr().ok()
+            .and_then(|addr| addr.as_pathname().map(|p| p.to_owned()))
+            .unwrap_or_else(PathBuf::new);
+        self.sockets.push(
+            Socket{addr: ListenAddr::Unix(path, None), lst: Listener::Unix(lst),
+                   adopted: true, group: 0, redirect: false, reuse_port: false});
+        self
+    }
+
+    /// Use inherited listening socket file descriptor.
+    ///
+    /// Tcp and unix domain sockets are supported. Server takes ownership
+    /// of the descriptor.
+    #[cfg(unix)]
+    pub fn listen_fd(self, fd: RawFd) -> io::Result<Self> {
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        if unsafe { libc::getsockname(
+            fd, &mut storage as *mut _ as *mut libc::sockaddr, &mut len) } != 0
+        {
+            return Err(io::Error::last_os_error())
+        }
+
+        // descriptor has to be listening stream socket
+        if get_sockopt(fd, libc::SO_TYPE)? != libc::SOCK_STREAM {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput, format!("Fd {} is not a stream socket", fd)))
+        }
+        if get_sockopt(fd, libc::SO_ACCEPTCONN)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput, format!("Fd {} is not a listening socket", fd)))
+        }
+
+        match i32::from(storage.ss_family) {
+            libc::AF_INET | libc::AF_INET6 =>
+                Ok(self.listen(unsafe { net::TcpListener::from_raw_fd(fd) })),
+            libc::AF_UNIX =>
+                Ok(self.listen_uds(unsafe { unix::UnixListener::from_raw_fd(fd) })),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput, format!("Unsupported socket family of fd {}", fd))),
+        }
+    }
+
+    /// Use listening sockets passed by systemd socket activation.
+    ///
+    /// Sockets are passed with `LISTEN_FDS` and `LISTEN_PID` environment variables.
+    /// If process is not socket activated, server is returned unchanged.
+    /// Environment variables get removed, so child processes do not inherit them.
+    ///
+    /// ```rust,ignore
+    /// let srv = HttpServer::new(|| Application::new())
+    ///     .listen_systemd()?;
+    /// let srv = if srv.addrs().is_empty() {
+    ///     srv.bind("127.0.0.1:8080")?  // <- not socket activated
+    /// } else {
+    ///     srv
+    /// };
+    /// ```
+    #[cfg(unix)]
+    pub fn listen_systemd(mut self) -> io::Result<Self> {
+        const SD_LISTEN_FDS_START: RawFd = 3;
+
+        let pid = env::var("LISTEN_PID").ok().and_then(|pid| pid.parse::<libc::pid_t>().ok());
+        let fds = env::var("LISTEN_FDS").ok().and_then(|fds| fds.parse::<RawFd>().ok());
+        env::remove_var("LISTEN_PID");
+        env::remove_var("LISTEN_FDS");
+        env::remove_var("LISTEN_FDNAMES");
+
+        if let (Some(pid), Some(fds)) = (pid, fds) {
+            if pid == unsafe { libc::getpid() } {
+                for fd in SD_LISTEN_FDS_START..SD_LISTEN_FDS_START + fds {
+                    self = self.listen_fd(fd)?;
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Use listening sockets handed over by previous server process.
+    ///
+    /// Check [`HandoffServer`](./dev/struct.HandoffServer.html) documentation
+    /// for more information. If process is not started by `HandoffServer`,
+    /// server is returned unchanged. Previous process gets notified
+    /// as soon as server starts accepting connections.
+    #[cfg(unix)]
+    pub fn listen_inherited(mut self) -> io::Result<Self> {
+        if let Some(fds) = env::var_os(LISTEN_FDS_ENV) {
+            env::remove_var(LISTEN_FDS_ENV);
+            for fd in fds.to_string_lossy().split(',') {
+                let fd = fd.parse::<RawFd>().map_err(|_| io::Error::new(
+                    io::ErrorKind::InvalidInput, format!("Invalid socket descriptor: {}", fd)))?;
+                self = self.listen_fd(fd)?;
+            }
+        }
+        if let Some(fd) = env::var_os(READY_FD_ENV) {
+            env::remove_var(READY_FD_ENV);
+            self.ready_fd = fd.to_string_lossy().parse().ok();
+        }
+        Ok(self)
+    }
+
+    /// Notify previous server process that this server accepts connections
+    #[cfg(unix)]
+    fn notify_ready(&mut self) {
+        if let Some(fd) = self.ready_fd.take() {
+            unsafe {
+                libc::write(fd, b"1".as_ptr() as *const libc::c_void, 1);
+                libc::close(fd);
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn notify_ready(&mut self) {}
+
+    fn start_workers(&mut self, settings: &ServerSettings, handler: &StreamHandlerType,
+                     sockets: &mut Vec<Socket>) -> Vec<WorkerClient>
     {
+        // with SO_REUSEPORT workers accept connections on bound tcp sockets
+        let mut reuse = Vec::new();
+        if self.reuse_port {
+            let mut idx = 0;
+            while idx < sockets.len() {
+                if sockets[idx].adopted || sockets[idx].redirect ||
+                    sockets[idx].addr.tcp().is_none()
+                {
+                    idx += 1;
+                    continue
+                }
+                // socket is bound before `reuse_port()` call
+                if !sockets[idx].reuse_port {
+                    warn!("Socket {} is bound without SO_REUSEPORT, using accept thread",
+                          sockets[idx].addr);
+                    idx += 1;
+                    continue
+                }
+                let sock = sockets.remove(idx);
+                if let (Some(addr), Listener::Tcp(lst)) = (sock.addr.tcp(), sock.lst) {
+                    info!("Starting http server on {} with SO_REUSEPORT", addr);
+                    reuse.push((addr, lst, sock.group));
+                }
+            }
+        }
+        let addrs: Vec<_> = reuse.iter().map(|&(addr, _, group)| (addr, group)).collect();
+        let redirect = sockets.iter().any(|sock| sock.redirect);
+
         // start workers
         let mut workers = Vec::new();
-        for _ in 0..self.threads {
+        for idx in 0..self.threads {
             let s = settings.clone();
-            let (tx, rx) = mpsc::unbounded::<Conn<net::TcpStream>>();
+
+            // first worker uses bound sockets
+            let listeners: Vec<_> = if idx == 0 {
+                reuse.drain(..).collect()
+            } else {
+                addrs.iter().filter_map(|&(addr, group)| {
+                    match create_tcp_listener(addr, self.backlog, true) {
+                        Ok(lst) => Some((addr, lst, group)),
+                        Err(err) => {
+                            error!("Can not create SO_REUSEPORT listener on {}: {}", addr, err);
+                            None
+                        }
+                    }
+                }).collect()
+            };
+            let backlog = self.backlog;
+            let maxconn = self.maxconn;
+            let proxy = self.proxy_protocol;
+            let limits = self.limits.clone();
+            let http2 = self.http2.clone();
+            let (client_timeout, min_body_rate) = (self.client_timeout, self.min_body_rate);
+            let (write_timeout, max_requests) = (self.write_timeout, self.max_requests);
+            let on_connect = self.on_connect.clone();
+            let (tx, rx) = mpsc::unbounded::<Conn<StdStream>>();
+            let load = Arc::new(AtomicUsize::new(0));
+            let load2 = Arc::clone(&load);
 
             let h = handler.clone();
+            let secure = handler.is_secure();
             let ka = self.keep_alive;
             let factory = Arc::clone(&self.factory);
+            let factories = self.factories.clone();
+            let acme = self.acme_dir.clone();
             let addr = Arbiter::start(move |ctx: &mut Context<_>| {
-                let apps: Vec<_> = (*factory)()
+                // settings for each application group
+                let apps: Vec<Box<HttpHandler>> = (*factory)()
                     .into_iter()
-                    .map(|h| h.into_handler(s.clone())).collect();
+                    .map(|h| Box::new(h.into_handler(s.clone())) as Box<HttpHandler>)
+                    .collect();
+                let groups = factories.iter().map(|factory| (*factory)(s.clone()));
+                let settings = Some(apps).into_iter().chain(groups).map(|apps| {
+                    WorkerSettings::new(apps, ka)
+                        .secure(secure)
+                        .proxy_protocol(proxy)
+                        .h1_limits(limits.clone())
+                        .h2_settings(http2.clone())
+                        .client_timeouts(client_timeout, min_body_rate)
+                        .write_timeout(write_timeout)
+                        .max_requests(max_requests)
+                        .on_connect(on_connect.clone())
+                }).collect();
+                let redirect = if redirect {
+                    let app = redirect_application(acme).into_handler(s.clone());
+                    Some(WorkerSettings::new(vec![app], ka)
+                         .proxy_protocol(proxy)
+                         .h1_limits(limits.clone())
+                         .client_timeouts(client_timeout, min_body_rate)
+                         .write_timeout(write_timeout)
+                         .max_requests(max_requests)
+                         .on_connect(on_connect.clone()))
+                } else {
+                    None
+                };
                 ctx.add_stream(rx);
-                Worker::new(apps, h, ka)
+                Worker::new(settings, h, load2)
+                    .redirect(redirect)
+                    .reuse_port(listeners, backlog, maxconn)
             });
-            workers.push(tx);
+            workers.push(WorkerClient{tx: tx, load: load});
             self.workers.push(addr);
         }
         info!("Starting {} http workers", self.threads);
@@ -291,7 +895,8 @@ impl<H: HttpHandler, U, V> HttpServer<TcpStream, net::SocketAddr, H, U>
     /// This method starts number of http handler workers in seperate threads.
     /// For each address this method starts separate thread which does `accept()` in a loop.
     ///
-    /// This methods panics if no socket addresses get bound.
+    /// This methods panics if no socket addresses get bound or if
+    /// `redirect_http_to_https()` is used, there is no https listener to redirect to.
     ///
     /// This method requires to run within properly configured `Actix` system.
     ///
@@ -317,18 +922,21 @@ impl<H: HttpHandler, U, V> HttpServer<TcpStream, net::SocketAddr, H, U>
     {
         if self.sockets.is_empty() {
             panic!("HttpServer::bind() has to be called befor start()");
+        } else if self.sockets.iter().any(|sock| sock.redirect) {
+            panic!("HttpServer::redirect_http_to_https() requires start_ssl() or start_tls()");
         } else {
-            let addrs: Vec<(net::SocketAddr, net::TcpListener)> =
-                self.sockets.drain().collect();
-            let settings = ServerSettings::new(Some(addrs[0].0), &self.host, false);
-            let workers = self.start_workers(&settings, &StreamHandlerType::Normal);
+            let mut sockets: Vec<Socket> = self.sockets.drain(..).collect();
+            let settings = ServerSettings::new(tcp_addr(&sockets), &self.host, false);
+            let workers = self.start_workers(&settings, &StreamHandlerType::Normal, &mut sockets);
 
             // start acceptors threads
-            for (addr, sock) in addrs {
-                info!("Starting http server on {}", addr);
-                self.accept.push(
-                    start_accept_thread(sock, addr, self.backlog, workers.clone()));
+            for sock in sockets {
+                info!("Starting http server on {}", sock.addr);
+                self.accept.push(start_accept_thread(
+                    sock, self.backlog,
+                    Dispatcher::new(self.dispatch, self.maxconn, workers.clone())));
             }
+            self.notify_ready();
 
             // start http server actor
             HttpServer::create(|_| {self})
@@ -384,8 +992,16 @@ impl<H: HttpHandler, U, V> HttpServer<TlsStream<TcpStream>, net::SocketAddr, H,
         if self.sockets.is_empty() {
             Err(io::Error::new(io::ErrorKind::Other, "No socket addresses are bound"))
         } else {
-            let addrs: Vec<(net::SocketAddr, net::TcpListener)> = self.sockets.drain().collect();
-            let settings = ServerSettings::new(Some(addrs[0].0), &self.host, false);
+            // native-tls does not support client certificates
+            #[cfg(feature="alpn")]
+            {
+                if self.client_auth.is_some() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other, "Client authentication requires start_ssl()"))
+                }
+            }
+            let mut sockets: Vec<Socket> = self.sockets.drain(..).collect();
+            let settings = ServerSettings::new(tcp_addr(&sockets), &self.host, false);
             let acceptor = match TlsAcceptor::builder(pkcs12) {
                 Ok(builder) => {
                     match builder.build() {
@@ -395,14 +1011,17 @@ impl<H: HttpHandler, U, V> HttpServer<TlsStream<TcpStream>, net::SocketAddr, H,
                 }
                 Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err))
             };
-            let workers = self.start_workers(&settings, &StreamHandlerType::Tls(acceptor));
+            let workers = self.start_workers(
+                &settings, &StreamHandlerType::Tls(acceptor), &mut sockets);
 
             // start acceptors threads
-            for (addr, sock) in addrs {
-                info!("Starting tls http server on {}", addr);
-                self.accept.push(
-                    start_accept_thread(sock, addr, self.backlog, workers.clone()));
+            for sock in sockets {
+                info!("Starting tls http server on {}", sock.addr);
+                self.accept.push(start_accept_thread(
+                    sock, self.backlog,
+                    Dispatcher::new(self.dispatch, self.maxconn, workers.clone())));
             }
+            self.notify_ready();
 
             // start http server actor
             Ok(HttpServer::create(|_| {self}))
@@ -422,27 +1041,24 @@ impl<H: HttpHandler, U, V> HttpServer<SslStream<TcpStream>, net::SocketAddr, H,
         if self.sockets.is_empty() {
             Err(io::Error::new(io::ErrorKind::Other, "No socket addresses are bound"))
         } else {
-            let addrs: Vec<(net::SocketAddr, net::TcpListener)> = self.sockets.drain().collect();
-            let settings = ServerSettings::new(Some(addrs[0].0), &self.host, false);
-            let acceptor = match SslAcceptorBuilder::mozilla_intermediate(
-                SslMethod::tls(), &identity.pkey, &identity.cert, &identity.chain)
-            {
-                Ok(mut builder) => {
-                    match builder.set_alpn_protocols(&[b"h2", b"http/1.1"]) {
-                        Ok(_) => builder.build(),
-                        Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err)),
-                    }
-                },
-                Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err))
-            };
-            let workers = self.start_workers(&settings, &StreamHandlerType::Alpn(acceptor));
+            let mut sockets: Vec<Socket> = self.sockets.drain(..).collect();
+            let settings = ServerSettings::new(tcp_addr(&sockets), &self.host, false);
+            let acceptor = ssl_acceptor(
+                &identity.pkey, &identity.cert, &identity.chain,
+                self.client_auth.as_ref(), &self.sni)?;
+            let acceptor = Arc::new(RwLock::new(acceptor));
+            self.ssl = Some(Arc::clone(&acceptor));
+            let workers = self.start_workers(
+                &settings, &StreamHandlerType::Alpn(acceptor), &mut sockets);
 
             // start acceptors threads
-            for (addr, sock) in addrs {
-                info!("Starting tls http server on {}", addr);
-                self.accept.push(
-                    start_accept_thread(sock, addr, self.backlog, workers.clone()));
+            for sock in sockets {
+                info!("Starting tls http server on {}", sock.addr);
+                self.accept.push(start_accept_thread(
+                    sock, self.backlog,
+                    Dispatcher::new(self.dispatch, self.maxconn, workers.clone())));
             }
+            self.notify_ready();
 
             // start http server actor
             Ok(HttpServer::create(|_| {self}))
@@ -450,6 +1066,135 @@ impl<H: HttpHandler, U, V> HttpServer<SslStream<TcpStream>, net::SocketAddr, H,
     }
 }
 
+/// Application of redirecting listener
+fn redirect_application(acme: Option<::std::path::PathBuf>) -> Application {
+    let mut app = Application::new();
+    if let Some(dir) = acme {
+        app = app.resource("/.well-known/acme-challenge/{token}",
+                           move |r| r.h(StaticFiles::new("token", dir, false)));
+    }
+    app.default_resource(|r| r.f(redirect_https))
+}
+
+/// Redirect request to the same url with `https` scheme
+fn redirect_https(req: HttpRequest) -> Result<HttpResponse, HttpError> {
+    let mut url = {
+        let host = req.connection_info().host();
+
+        // https uses default port
+        let host = match host.rfind(':') {
+            Some(idx) if !host[idx..].contains(']') => &host[..idx],
+            _ => host,
+        };
+        format!("https://{}{}", host, req.path())
+    };
+    if !req.query_string().is_empty() {
+        url.push('?');
+        url.push_str(req.query_string());
+    }
+    httpcodes::HTTPMovedPermanenty.build()
+        .header(header::LOCATION, url.as_str())
+        .finish()
+}
+
+/// Cipher list of mozilla intermediate configuration
+#[cfg(feature="alpn")]
+const SSL_CIPHERS: &str =
+    "ECDHE-ECDSA-CHACHA20-POLY1305:ECDHE-RSA-CHACHA20-POLY1305:\
+     ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:\
+     ECDHE-ECDSA-AES256-GCM-SHA384:ECDHE-RSA-AES256-GCM-SHA384:\
+     DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:ECDHE-ECDSA-AES128-SHA256:\
+     ECDHE-RSA-AES128-SHA256:ECDHE-ECDSA-AES128-SHA:ECDHE-RSA-AES256-SHA384:\
+     ECDHE-RSA-AES128-SHA:ECDHE-ECDSA-AES256-SHA384:ECDHE-ECDSA-AES256-SHA:\
+     ECDHE-RSA-AES256-SHA:DHE-RSA-AES128-SHA256:DHE-RSA-AES128-SHA:DHE-RSA-AES256-SHA256:\
+     DHE-RSA-AES256-SHA:ECDHE-ECDSA-DES-CBC3-SHA:ECDHE-RSA-DES-CBC3-SHA:\
+     EDH-RSA-DES-CBC3-SHA:AES128-GCM-SHA256:AES256-GCM-SHA384:AES128-SHA256:AES256-SHA256:\
+     AES128-SHA:AES256-SHA:DES-CBC3-SHA:!DSS";
+
+/// ffdhe2048 parameters of mozilla intermediate configuration
+#[cfg(feature="alpn")]
+const SSL_DHPARAM: &str = "
+-----BEGIN DH PARAMETERS-----
+MIIBCAKCAQEA//////////+t+FRYortKmq/cViAnPTzx2LnFg84tNpWp4TZBFGQz
++8yTnc4kmz75fS/jY2MMddj2gbICrsRhetPfHtXV/WVhJDP1H18GbtCFY2VVPe0a
+87VXE15/V8k1mE8McODmi3fipona8+/och3xWKE2rec1MKzKT0g6eXq8CrGCsyT7
+YdEIqUuyyOP7uWrat2DX9GgdT0Kj3jlN9K5W7edjcrsZCwenyO4KbXCeAvzhzffi
+7MA0BM0oNC9hkXL+nOmFg/+OTxIy7vKBg8P+OxtMb61zO7X8vC7CIAXFjvGDfRaD
+ssbzSibBsu/6iGtCOGEoXJf//////////wIBAg==
+-----END DH PARAMETERS-----
+";
+
+/// Configure tls context with certificate, "h2" and "http/1.1" alpn protocols
+/// and client authentication.
+///
+/// Connection switches to server name context during handshake, so default
+/// acceptor and server name contexts are configured with this function.
+#[cfg(feature="alpn")]
+fn configure_ssl<I>(ctx: &mut SslContextBuilder, pkey: &PKeyRef, cert: &X509Ref, chain: I,
+                    auth: Option<&ClientAuth>) -> Result<(), ErrorStack>
+    where I: IntoIterator, I::Item: AsRef<X509Ref>
+{
+    ctx.set_options(SSL_OP_NO_SSLV2 | SSL_OP_NO_SSLV3 | SSL_OP_NO_COMPRESSION |
+                    SSL_OP_NO_TICKET | SSL_OP_SINGLE_DH_USE | SSL_OP_SINGLE_ECDH_USE |
+                    SSL_OP_CIPHER_SERVER_PREFERENCE);
+    ctx.set_cipher_list(SSL_CIPHERS)?;
+    ctx.set_tmp_dh(&Dh::from_pem(SSL_DHPARAM.as_bytes())?)?;
+
+    ctx.set_private_key(pkey)?;
+    ctx.set_certificate(cert)?;
+    ctx.check_private_key()?;
+    for cert in chain {
+        ctx.add_extra_chain_cert(cert.as_ref().to_owned())?;
+    }
+    if let Some(auth) = auth {
+        auth.configure(ctx)?;
+    }
+    ctx.set_alpn_protocols(&[b"h2", b"http/1.1"])
+}
+
+/// Create tls acceptor, certificate is selected by server name
+#[cfg(feature="alpn")]
+fn ssl_acceptor<I>(pkey: &PKeyRef, cert: &X509Ref, chain: I,
+                   auth: Option<&ClientAuth>, sni: &Arc<HashMap<String, SslContext>>)
+                   -> io::Result<SslAcceptor>
+    where I: IntoIterator, I::Item: AsRef<X509Ref>
+{
+    let mut builder = SslAcceptorBuilder::mozilla_intermediate_raw(SslMethod::tls())
+        .and_then(|mut builder| {
+            configure_ssl(builder.builder_mut(), pkey, cert, chain, auth)?;
+            Ok(builder)
+        })
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    // select certificate by server name
+    if !sni.is_empty() {
+        let sni = Arc::clone(sni);
+        builder.builder_mut().set_servername_callback(move |ssl| {
+            let ctx = match ssl.servername() {
+                Some(name) => sni.get(&name.to_lowercase()),
+                None => None,
+            };
+            if let Some(ctx) = ctx {
+                if let Err(err) = ssl.set_ssl_context(ctx) {
+                    error!("Can not set tls certificate for server name: {}", err);
+                }
+            }
+            Ok(())
+        });
+    }
+    Ok(builder.build())
+}
+
+/// Create tls context with certificate for server name
+#[cfg(feature="alpn")]
+fn sni_context(identity: &ParsedPkcs12, auth: Option<&ClientAuth>)
+               -> Result<SslContext, ErrorStack>
+{
+    let mut builder = SslContextBuilder::new(SslMethod::tls())?;
+    configure_ssl(&mut builder, &identity.pkey, &identity.cert, &identity.chain, auth)?;
+    Ok(builder.build())
+}
+
 impl<T, A, H, U, V> HttpServer<T, A, H, U>
     where A: 'static,
           T: AsyncRead + AsyncWrite + 'static,
@@ -460,21 +1205,28 @@ impl<T, A, H, U, V> HttpServer<T, A, H, U>
     /// Start listening for incomming connections from a stream.
     ///
     /// This method uses only one thread for handling incoming connections.
+    ///
+    /// Listener of `redirect_http_to_https()` requires `secure` stream,
+    /// this method panics otherwise.
     pub fn start_incoming<S>(mut self, stream: S, secure: bool) -> SyncAddress<Self>
         where S: Stream<Item=(T, A), Error=io::Error> + 'static
     {
+        if !secure && self.sockets.iter().any(|sock| sock.redirect) {
+            panic!("HttpServer::redirect_http_to_https() requires secure incoming stream");
+        }
         if !self.sockets.is_empty() {
-            let addrs: Vec<(net::SocketAddr, net::TcpListener)> =
-                self.sockets.drain().collect();
-            let settings = ServerSettings::new(Some(addrs[0].0), &self.host, false);
-            let workers = self.start_workers(&settings, &StreamHandlerType::Normal);
+            let mut sockets: Vec<Socket> = self.sockets.drain(..).collect();
+            let settings = ServerSettings::new(tcp_addr(&sockets), &self.host, false);
+            let workers = self.start_workers(&settings, &StreamHandlerType::Normal, &mut sockets);
 
             // start acceptors threads
-            for (addr, sock) in addrs {
-                info!("Starting http server on {}", addr);
-                self.accept.push(
-                    start_accept_thread(sock, addr, self.backlog, workers.clone()));
+            for sock in sockets {
+                info!("Starting http server on {}", sock.addr);
+                self.accept.push(start_accept_thread(
+                    sock, self.backlog,
+                    Dispatcher::new(self.dispatch, self.maxconn, workers.clone())));
             }
+            self.notify_ready();
         }
 
         // set server settings
@@ -483,12 +1235,20 @@ impl<T, A, H, U, V> HttpServer<T, A, H, U>
         let apps: Vec<_> = (*self.factory)()
             .into_iter()
             .map(|h| h.into_handler(settings.clone())).collect();
-        self.h = Some(Rc::new(WorkerSettings::new(apps, self.keep_alive)));
+        self.h = Some(Rc::new(
+            WorkerSettings::new(apps, self.keep_alive)
+                .secure(secure)
+                .h1_limits(self.limits.clone())
+                .h2_settings(self.http2.clone())
+                .client_timeouts(self.client_timeout, self.min_body_rate)
+                .write_timeout(self.write_timeout)
+                .max_requests(self.max_requests)
+                .on_connect(self.on_connect.clone())));
 
         // start server
         HttpServer::create(move |ctx| {
             ctx.add_stream(stream.map(
-                move |(t, _)| Conn{io: t, peer: None, http2: false}));
+                move |(t, _)| Conn{io: t, peer: None, http2: false, group: 0, redirect: false}));
             self
         })
     }
@@ -548,7 +1308,8 @@ impl<T, A, H, U> Handler<Conn<T>, io::Error> for HttpServer<T, A, H, U>
     fn handle(&mut self, msg: Conn<T>, _: &mut Context<Self>) -> Response<Self, Conn<T>>
     {
         Arbiter::handle().spawn(
-            HttpChannel::new(Rc::clone(self.h.as_ref().unwrap()), msg.io, msg.peer, msg.http2));
+            HttpChannel::new(Rc::clone(self.h.as_ref().unwrap()),
+                             msg.io, Connection::new(msg.peer, msg.http2)));
         Self::empty()
     }
 }
@@ -584,6 +1345,11 @@ impl<T, A, H, U> Handler<PauseServer> for HttpServer<T, A, H, U>
             let _ = item.1.send(Command::Pause);
             let _ = item.0.set_readiness(mio::Ready::readable());
         }
+        if self.reuse_port {
+            for worker in &self.workers {
+                worker.send(PauseWorker);
+            }
+        }
         Self::empty()
     }
 }
@@ -600,6 +1366,11 @@ impl<T, A, H, U> Handler<ResumeServer> for HttpServer<T, A, H, U>
             let _ = item.1.send(Command::Resume);
             let _ = item.0.set_readiness(mio::Ready::readable());
         }
+        if self.reuse_port {
+            for worker in &self.workers {
+                worker.send(ResumeWorker);
+            }
+        }
         Self::empty()
     }
 }
@@ -656,27 +1427,621 @@ impl<T, A, H, U> Handler<StopServer> for HttpServer<T, A, H, U>
     }
 }
 
+/// Replace tls certificate of server started with `start_ssl()`.
+///
+/// New certificate is used for new tls handshakes in all workers,
+/// established connections are not affected.
+///
+/// ```rust,ignore
+/// let msg = dev::ReloadSsl::Pem(fs::read("cert.pem")?, fs::read("key.pem")?);
+/// addr.call_fut(msg).wait()??;
+/// ```
+#[cfg(feature="alpn")]
+pub enum ReloadSsl {
+    /// DER encoded PKCS#12 archive and its password
+    Pkcs12(Vec<u8>, String),
+    /// PEM encoded certificate chain, starting with server certificate,
+    /// and PEM encoded private key
+    Pem(Vec<u8>, Vec<u8>),
+}
+
+#[cfg(feature="alpn")]
+impl ResponseType for ReloadSsl {
+    type Item = ();
+    type Error = io::Error;
+}
+
+#[cfg(feature="alpn")]
+impl ReloadSsl {
+    fn acceptor(&self, auth: Option<&ClientAuth>, sni: &Arc<HashMap<String, SslContext>>)
+                -> io::Result<SslAcceptor>
+    {
+        match *self {
+            ReloadSsl::Pkcs12(ref der, ref password) => {
+                let identity = Pkcs12::from_der(der)
+                    .and_then(|pkcs12| pkcs12.parse(password))
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                ssl_acceptor(&identity.pkey, &identity.cert, &identity.chain, auth, sni)
+            }
+            ReloadSsl::Pem(ref cert, ref key) => {
+                let mut chain = X509::stack_from_pem(cert)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                let pkey = PKey::private_key_from_pem(key)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                if chain.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData, "No certificates are found"))
+                }
+                let cert = chain.remove(0);
+                ssl_acceptor(&pkey, &cert, &chain, auth, sni)
+            }
+        }
+    }
+}
+
+#[cfg(feature="alpn")]
+impl<T, A, H, U> Handler<ReloadSsl> for HttpServer<T, A, H, U>
+    where T: AsyncRead + AsyncWrite + 'static,
+          H: HttpHandler + 'static,
+          U: 'static,
+          A: 'static,
+{
+    fn handle(&mut self, msg: ReloadSsl, _: &mut Context<Self>) -> Response<Self, ReloadSsl>
+    {
+        let res = if let Some(ref ssl) = self.ssl {
+            msg.acceptor(self.client_auth.as_ref(), &self.sni).map(|acceptor| {
+                *ssl.write().unwrap() = acceptor;
+                info!("Tls certificate is reloaded");
+            })
+        } else {
+            Err(io::Error::new(io::ErrorKind::Other, "Server is not started with start_ssl()"))
+        };
+        if let Err(ref err) = res {
+            error!("Can not reload tls certificate: {}", err);
+        }
+        Self::async_reply(fut::result(res))
+    }
+}
+
+/// Hand over listening sockets to a new process and stop.
+///
+/// Server starts new process with duplicates of listening sockets, new process
+/// has to adopt them with `HttpServer::listen_inherited()` method. As soon as new
+/// server starts accepting connections, this server stops accepting connections
+/// and shutdowns gracefully, same as with `StopServer{graceful: true}`.
+/// If new process does not become ready within `timeout`, it gets killed
+/// and this server continues to serve requests. Server does not block
+/// while new process starts.
+///
+/// Handoff is not supported if server uses `SO_REUSEPORT` listeners,
+/// check `HttpServer::reuse_port()`.
+///
+/// Returns pid of the new process.
+///
+/// ```rust,ignore
+/// // re-execute updated binary
+/// let msg = dev::HandoffServer::reexec(Duration::from_secs(30))?;
+/// let pid = addr.call_fut(msg).wait()??;
+/// ```
+#[cfg(unix)]
+pub struct HandoffServer {
+    /// Executable of the new process
+    pub program: PathBuf,
+    /// Arguments of the new process
+    pub args: Vec<OsString>,
+    /// Time to wait until new server accepts connections
+    pub timeout: Duration,
+}
+
+#[cfg(unix)]
+impl HandoffServer {
+    /// Start new process with same executable path and arguments as current process.
+    pub fn reexec(timeout: Duration) -> io::Result<HandoffServer> {
+        let mut args = env::args_os();
+        let program = args.next().map(PathBuf::from).ok_or_else(
+            || io::Error::new(io::ErrorKind::NotFound, "Can not get current executable"))?;
+        Ok(HandoffServer {
+            program: program,
+            args: args.collect(),
+            timeout: timeout,
+        })
+    }
+}
+
+#[cfg(unix)]
+impl ResponseType for HandoffServer {
+    type Item = u32;
+    type Error = io::Error;
+}
+
+/// Start new process with duplicated listening sockets
+#[cfg(unix)]
+fn spawn_handoff(accept: &[(mio::SetReadiness, sync_mpsc::Sender<Command>)],
+                 msg: &HandoffServer) -> io::Result<(process::Child, RawFd)>
+{
+    let mut fds = Vec::new();
+    for item in accept {
+        let (tx, rx) = sync_mpsc::channel();
+        let _ = item.1.send(Command::Duplicate(tx));
+        let _ = item.0.set_readiness(mio::Ready::readable());
+
+        match rx.recv_timeout(Duration::new(1, 0)) {
+            Ok(Ok(fd)) => fds.push(fd),
+            Ok(Err(err)) => {
+                close_fds(&fds);
+                return Err(err)
+            }
+            Err(_) => {
+                close_fds(&fds);
+                return Err(io::Error::new(
+                    io::ErrorKind::Other, "Accept thread does not respond"))
+            }
+        }
+    }
+    if fds.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::Other, "No listening sockets"))
+    }
+
+    // new process notifies readiness via pipe, read end stays in this process
+    let pipe = match cloexec_pipe() {
+        Ok(pipe) => pipe,
+        Err(err) => {
+            close_fds(&fds);
+            return Err(err)
+        }
+    };
+
+    // all descriptors are close-on-exec, so processes spawned concurrently
+    // do not inherit them. flag is cleared in the new process only.
+    let mut inherited = fds.clone();
+    inherited.push(pipe[1]);
+    let listen_fds: Vec<String> = fds.iter().map(|fd| fd.to_string()).collect();
+    let child = process::Command::new(&msg.program)
+        .args(&msg.args)
+        .env(LISTEN_FDS_ENV, listen_fds.join(","))
+        .env(READY_FD_ENV, pipe[1].to_string())
+        .before_exec(move || {
+            for fd in &inherited {
+                if unsafe { libc::fcntl(*fd, libc::F_SETFD, 0) } < 0 {
+                    return Err(io::Error::last_os_error())
+                }
+            }
+            Ok(())
+        })
+        .spawn();
+
+    // new process owns its copies of descriptors
+    close_fds(&fds);
+    close_fds(&[pipe[1]]);
+
+    match child {
+        Ok(child) => Ok((child, pipe[0])),
+        Err(err) => {
+            close_fds(&[pipe[0]]);
+            Err(err)
+        }
+    }
+}
+
+/// Start new process and wait until it is ready, returns pid of the new process
+#[cfg(unix)]
+fn handoff(accept: &[(mio::SetReadiness, sync_mpsc::Sender<Command>)],
+           msg: &HandoffServer) -> io::Result<u32>
+{
+    let (mut child, ready) = spawn_handoff(accept, msg)?;
+    let pid = child.id();
+    info!("Started new server process {}, waiting for readiness", pid);
+
+    match wait_ready(ready, msg.timeout) {
+        Ok(()) => Ok(pid),
+        Err(err) => {
+            error!("Server process {} is not ready: {}", pid, err);
+            let _ = child.kill();
+            let _ = child.wait();
+            Err(err)
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<T, A, H, U> Handler<HandoffServer> for HttpServer<T, A, H, U>
+    where T: AsyncRead + AsyncWrite + 'static,
+          H: HttpHandler + 'static,
+          U: 'static,
+          A: 'static,
+{
+    fn handle(&mut self, msg: HandoffServer, _: &mut Context<Self>)
+              -> Response<Self, HandoffServer>
+    {
+        // SO_REUSEPORT listeners are owned by workers
+        if self.reuse_port {
+            let err = io::Error::new(
+                io::ErrorKind::Other, "Handoff of SO_REUSEPORT listeners is not supported");
+            error!("Can not start new server process: {}", err);
+            return Self::async_reply(fut::result(Err(err)))
+        }
+
+        // accept threads and new process get waited in separate thread
+        let accept: Vec<_> = self.accept.iter()
+            .map(|&(ref readiness, ref tx)| (readiness.clone(), tx.clone())).collect();
+        let (tx, rx) = oneshot::channel();
+        let _ = thread::Builder::new().name("Handoff".to_owned()).spawn(move || {
+            let _ = tx.send(handoff(&accept, &msg));
+        });
+
+        let fut = rx
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Handoff is canceled"))
+            .and_then(|res| res)
+            .actfuture();
+        Self::async_reply(ActorFuture::then(fut, move |res, slf: &mut Self, ctx| {
+            match res {
+                Ok(pid) => {
+                    info!("Server process {} is ready, stopping", pid);
+                    Handler::<StopServer>::handle(slf, StopServer{graceful: true}, ctx);
+                    fut::result(Ok(pid))
+                }
+                Err(err) => {
+                    error!("Can not hand over listening sockets: {}", err);
+                    fut::result(Err(err))
+                }
+            }
+        }))
+    }
+}
+
+/// Environment variable with socket descriptors passed to a new process
+#[cfg(unix)]
+const LISTEN_FDS_ENV: &str = "ACTIX_LISTEN_FDS";
+
+/// Environment variable with descriptor of readiness notification pipe
+#[cfg(unix)]
+const READY_FD_ENV: &str = "ACTIX_READY_FD";
+
+#[cfg(unix)]
+fn close_fds(fds: &[RawFd]) {
+    for fd in fds {
+        unsafe { libc::close(*fd) };
+    }
+}
+
+/// Create pipe with close-on-exec descriptors
+#[cfg(any(target_os="linux", target_os="android"))]
+fn cloexec_pipe() -> io::Result<[RawFd; 2]> {
+    let mut pipe = [0 as RawFd; 2];
+    if unsafe { libc::pipe2(pipe.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(pipe)
+    }
+}
+
+/// Create pipe with close-on-exec descriptors.
+///
+/// There is no `pipe2()`, flag is set right after pipe creation.
+#[cfg(all(unix, not(any(target_os="linux", target_os="android"))))]
+fn cloexec_pipe() -> io::Result<[RawFd; 2]> {
+    let mut pipe = [0 as RawFd; 2];
+    if unsafe { libc::pipe(pipe.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error())
+    }
+    for fd in &pipe {
+        unsafe { libc::fcntl(*fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+    }
+    Ok(pipe)
+}
+
+/// Wait until new process writes to readiness pipe
+#[cfg(unix)]
+fn wait_ready(fd: RawFd, timeout: Duration) -> io::Result<()> {
+    let mut pfd = libc::pollfd { fd: fd, events: libc::POLLIN, revents: 0 };
+    let ms = timeout.as_secs() * 1000 + u64::from(timeout.subsec_nanos() / 1_000_000);
+
+    let res = match unsafe { libc::poll(&mut pfd, 1, ms as libc::c_int) } {
+        n if n < 0 => Err(io::Error::last_os_error()),
+        0 => Err(io::Error::new(io::ErrorKind::TimedOut, "Readiness timeout")),
+        _ => {
+            let mut buf = [0u8; 1];
+            if unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, 1) } == 1 {
+                Ok(())
+            } else {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Process exited"))
+            }
+        }
+    };
+    close_fds(&[fd]);
+    res
+}
+
 enum Command {
     Pause,
     Resume,
     Stop,
+    /// Duplicate listening socket, used for handoff to a new process
+    #[cfg(unix)]
+    Duplicate(sync_mpsc::Sender<io::Result<RawFd>>),
+}
+
+/// Address of the listening socket
+#[derive(Clone, Debug)]
+enum ListenAddr {
+    Tcp(net::SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf, Option<u32>),
+}
+
+impl ListenAddr {
+    fn tcp(&self) -> Option<net::SocketAddr> {
+        match *self {
+            ListenAddr::Tcp(addr) => Some(addr),
+            #[cfg(unix)]
+            ListenAddr::Unix(..) => None,
+        }
+    }
+
+    /// Create new listening socket for this address
+    fn listen(&self, backlog: i32) -> io::Result<Listener> {
+        match *self {
+            ListenAddr::Tcp(addr) =>
+                Ok(Listener::Tcp(create_tcp_listener(addr, backlog, false)?)),
+            #[cfg(unix)]
+            ListenAddr::Unix(ref path, mode) =>
+                Ok(Listener::Unix(create_uds_listener(path, backlog, mode)?)),
+        }
+    }
+
+    /// Remove socket file, if any
+    #[cfg(unix)]
+    fn cleanup(&self) {
+        if let ListenAddr::Unix(ref path, _) = *self {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn cleanup(&self) {}
 }
 
-fn start_accept_thread(sock: net::TcpListener, addr: net::SocketAddr, backlog: i32,
-                       workers: Vec<mpsc::UnboundedSender<Conn<net::TcpStream>>>)
+impl fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ListenAddr::Tcp(ref addr) => write!(f, "{}", addr),
+            #[cfg(unix)]
+            ListenAddr::Unix(ref path, _) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+enum Listener {
+    Tcp(net::TcpListener),
+    #[cfg(unix)]
+    Unix(unix::UnixListener),
+}
+
+struct Socket {
+    addr: ListenAddr,
+    lst: Listener,
+    /// socket is not created by server, it can not be re-created
+    adopted: bool,
+    /// application group, zero is server's application factory
+    group: usize,
+    /// plain http listener, redirects requests to https
+    redirect: bool,
+    /// socket is created with `SO_REUSEPORT` option
+    reuse_port: bool,
+}
+
+/// First tcp address, used as server address in `ServerSettings`
+fn tcp_addr(sockets: &[Socket]) -> Option<net::SocketAddr> {
+    sockets.iter().filter(|s| !s.redirect).filter_map(|s| s.addr.tcp()).next()
+}
+
+/// Non-blocking listener used by accept thread
+enum MioListener {
+    Tcp(mio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(mio_uds::UnixListener),
+}
+
+impl MioListener {
+    fn new(lst: Listener) -> io::Result<MioListener> {
+        match lst {
+            Listener::Tcp(lst) =>
+                Ok(MioListener::Tcp(mio::net::TcpListener::from_std(lst)?)),
+            #[cfg(unix)]
+            Listener::Unix(lst) =>
+                Ok(MioListener::Unix(mio_uds::UnixListener::from_listener(lst)?)),
+        }
+    }
+
+    /// Accept new connection, returns `None` if there are no pending connections
+    fn accept(&self) -> io::Result<Option<(StdStream, Option<net::SocketAddr>)>> {
+        match *self {
+            MioListener::Tcp(ref lst) => match lst.accept_std() {
+                Ok((io, addr)) => Ok(Some((StdStream::Tcp(io), Some(addr)))),
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => Ok(None),
+                Err(err) => Err(err),
+            },
+            #[cfg(unix)]
+            MioListener::Unix(ref lst) =>
+                Ok(lst.accept_std()?.map(|(io, _)| (StdStream::Unix(io), None))),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl MioListener {
+    /// Duplicate socket descriptor, new descriptor is close-on-exec
+    fn dup(&self) -> io::Result<RawFd> {
+        let fd = match *self {
+            MioListener::Tcp(ref lst) => lst.as_raw_fd(),
+            MioListener::Unix(ref lst) => lst.as_raw_fd(),
+        };
+        let fd = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+        if fd < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(fd)
+        }
+    }
+}
+
+impl mio::Evented for MioListener {
+    fn register(&self, poll: &mio::Poll, token: mio::Token,
+                interest: mio::Ready, opts: mio::PollOpt) -> io::Result<()> {
+        match *self {
+            MioListener::Tcp(ref lst) => lst.register(poll, token, interest, opts),
+            #[cfg(unix)]
+            MioListener::Unix(ref lst) => lst.register(poll, token, interest, opts),
+        }
+    }
+
+    fn reregister(&self, poll: &mio::Poll, token: mio::Token,
+                  interest: mio::Ready, opts: mio::PollOpt) -> io::Result<()> {
+        match *self {
+            MioListener::Tcp(ref lst) => lst.reregister(poll, token, interest, opts),
+            #[cfg(unix)]
+            MioListener::Unix(ref lst) => lst.reregister(poll, token, interest, opts),
+        }
+    }
+
+    fn deregister(&self, poll: &mio::Poll) -> io::Result<()> {
+        match *self {
+            MioListener::Tcp(ref lst) => lst.deregister(poll),
+            #[cfg(unix)]
+            MioListener::Unix(ref lst) => lst.deregister(poll),
+        }
+    }
+}
+
+/// Strategy of distributing accepted connections between workers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispatch {
+    /// Workers receive connections in turn
+    RoundRobin,
+    /// Connection goes to the worker with the least number of open connections
+    LeastConnections,
+    /// Connections from the same peer ip address go to the same worker.
+    /// Connections without peer address are distributed round-robin.
+    PeerHash,
+}
+
+/// Accept thread side of a worker
+#[derive(Clone)]
+struct WorkerClient {
+    tx: mpsc::UnboundedSender<Conn<StdStream>>,
+    /// Number of open connections, incremented by accept thread
+    /// and decremented by worker
+    load: Arc<AtomicUsize>,
+}
+
+struct Dispatcher {
+    dispatch: Dispatch,
+    workers: Vec<WorkerClient>,
+    next: usize,
+    maxconn: Option<usize>,
+}
+
+impl Dispatcher {
+    fn new(dispatch: Dispatch, maxconn: Option<usize>, workers: Vec<WorkerClient>) -> Dispatcher
+    {
+        Dispatcher{dispatch: dispatch, workers: workers, next: 0, maxconn: maxconn}
+    }
+
+    fn send(&mut self, msg: Conn<StdStream>) {
+        let idx = self.select(msg.peer.as_ref());
+        let worker = &self.workers[idx];
+        worker.load.fetch_add(1, Ordering::Relaxed);
+        worker.tx.unbounded_send(msg).expect("worker thread died");
+    }
+
+    fn load(&self, idx: usize) -> usize {
+        self.workers[idx].load.load(Ordering::Relaxed)
+    }
+
+    /// Index of least loaded worker, scan starts from `next` worker
+    fn least_loaded(&self) -> usize {
+        let num = self.workers.len();
+        let mut idx = self.next;
+        let mut min = self.load(idx);
+        for i in 1..num {
+            let n = (self.next + i) % num;
+            let load = self.load(n);
+            if load < min {
+                idx = n;
+                min = load;
+            }
+        }
+        idx
+    }
+
+    fn select(&mut self, peer: Option<&net::SocketAddr>) -> usize {
+        let num = self.workers.len();
+        let idx = match (self.dispatch, peer) {
+            (Dispatch::PeerHash, Some(peer)) => {
+                let mut hasher = DefaultHasher::new();
+                peer.ip().hash(&mut hasher);
+                (hasher.finish() % num as u64) as usize
+            },
+            // scan starts from next worker, so idle workers get
+            // connections in turn during accept bursts
+            (Dispatch::LeastConnections, _) => self.least_loaded(),
+            _ => self.next,
+        };
+
+        // full worker does not get new connections
+        let idx = match self.maxconn {
+            Some(max) if self.load(idx) >= max => self.least_loaded(),
+            _ => idx,
+        };
+        self.next = (idx + 1) % num;
+        idx
+    }
+
+    /// All workers reached connections limit
+    fn is_full(&self) -> bool {
+        if let Some(max) = self.maxconn {
+            (0..self.workers.len()).all(|idx| self.load(idx) >= max)
+        } else {
+            false
+        }
+    }
+
+    /// Some worker dropped below low-water mark
+    fn is_available(&self) -> bool {
+        if let Some(max) = self.maxconn {
+            let low = max - max / 10;
+            (0..self.workers.len()).any(|idx| self.load(idx) < low)
+        } else {
+            true
+        }
+    }
+}
+
+/// Error is related to accepted connection, not to listening socket
+pub(crate) fn connection_error(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::ConnectionRefused ||
+        err.kind() == io::ErrorKind::ConnectionAborted ||
+        err.kind() == io::ErrorKind::ConnectionReset ||
+        err.kind() == io::ErrorKind::Interrupted
+}
+
+fn start_accept_thread(sock: Socket, backlog: i32, mut workers: Dispatcher)
                        -> (mio::SetReadiness, sync_mpsc::Sender<Command>)
 {
     let (tx, rx) = sync_mpsc::channel();
     let (reg, readiness) = mio::Registration::new2();
+    let Socket { addr, lst, adopted, group, redirect, .. } = sock;
 
     // start accept thread
     let _ = thread::Builder::new().name(format!("Accept on {}", addr)).spawn(move || {
         const SRV: mio::Token = mio::Token(0);
         const CMD: mio::Token = mio::Token(1);
 
+        #[cfg_attr(not(unix), allow(unused_mut))]
+        let mut adopted = adopted;
+
         let mut server = Some(
-            mio::net::TcpListener::from_listener(sock, &addr)
-                .expect("Can not create mio::net::TcpListener"));
+            MioListener::new(lst).expect("Can not create mio listener"));
 
         // Create a poll instance
         let poll = match mio::Poll::new() {
@@ -698,54 +2063,106 @@ fn start_accept_thread(sock: net::TcpListener, addr: net::SocketAddr, backlog: i
             panic!("Can not register Registration: {}", err);
         }
 
+        // Adopted socket is kept open while server is paused
+        let mut paused = None;
+
+        // Socket is deregistered while all workers are full
+        let mut backpressure = false;
+
         // Create storage for events
         let mut events = mio::Events::with_capacity(128);
 
-        let mut next = 0;
         loop {
-            if let Err(err) = poll.poll(&mut events, None) {
+            // workers do not notify accept thread, check their load periodically
+            let timeout = if backpressure { Some(Duration::from_millis(100)) } else { None };
+            if let Err(err) = poll.poll(&mut events, timeout) {
                 panic!("Poll error: {}", err);
             }
 
+            if backpressure && workers.is_available() {
+                backpressure = false;
+                if let Some(ref server) = server {
+                    if let Err(err) = poll.register(
+                        server, SRV, mio::Ready::readable(), mio::PollOpt::edge())
+                    {
+                        error!("Can not resume socket accept process: {}", err);
+                    } else {
+                        info!("Resumed accepting connections on {}", addr);
+                    }
+                }
+            }
+
             for event in events.iter() {
                 match event.token() {
                     SRV => {
                         if let Some(ref server) = server {
                             loop {
-                                match server.accept_std() {
-                                    Ok((sock, addr)) => {
-                                        let msg = Conn{
-                                            io: sock, peer: Some(addr), http2: false};
-                                        workers[next].unbounded_send(msg)
-                                            .expect("worker thread died");
-                                        next = (next + 1) % workers.len();
-                                    },
-                                    Err(err) => if err.kind() == io::ErrorKind::WouldBlock {
-                                        break
+                                if workers.is_full() {
+                                    if let Err(err) = poll.deregister(server) {
+                                        error!("Can not deregister server socket {}", err);
                                     } else {
+                                        backpressure = true;
+                                        info!("Connections limit is reached, \
+                                               stop accepting connections on {}", addr);
+                                    }
+                                    break
+                                }
+                                match server.accept() {
+                                    Ok(Some((io, peer))) => {
+                                        workers.send(Conn{io: io, peer: peer, http2: false,
+                                                          group: group, redirect: redirect});
+                                    },
+                                    Ok(None) => break,
+                                    Err(ref err) if connection_error(err) => continue,
+                                    Err(err) => {
+                                        // i.e. EMFILE, retry after short delay
                                         error!("Error accepting connection: {:?}", err);
-                                        return
+                                        if let Err(err) = poll.deregister(server) {
+                                            error!("Can not deregister server socket {}", err);
+                                        } else {
+                                            backpressure = true;
+                                        }
+                                        break
                                     }
                                 }
                             }
                         }
                     },
-                    CMD => match rx.try_recv() {
-                        Ok(cmd) => match cmd {
+                    CMD => loop {
+                        let cmd = match rx.try_recv() {
+                            Ok(cmd) => cmd,
+                            Err(sync_mpsc::TryRecvError::Empty) => break,
+                            Err(sync_mpsc::TryRecvError::Disconnected) => {
+                                server.take();
+                                if !adopted {
+                                    addr.cleanup();
+                                }
+                                return
+                            },
+                        };
+                        match cmd {
                             Command::Pause => if let Some(server) = server.take() {
-                                if let Err(err) = poll.deregister(&server) {
+                                if backpressure {
+                                    // socket is not registered
+                                    backpressure = false;
+                                } else if let Err(err) = poll.deregister(&server) {
                                     error!("Can not deregister server socket {}", err);
-                                } else {
-                                    info!("Paused accepting connections on {}", addr);
+                                }
+                                info!("Paused accepting connections on {}", addr);
+                                if adopted {
+                                    paused = Some(server);
                                 }
                             },
-                            Command::Resume => {
-                                let lst = create_tcp_listener(addr, backlog)
-                                    .expect("Can not create net::TcpListener");
-
-                                server = Some(
-                                    mio::net::TcpListener::from_listener(lst, &addr)
-                                        .expect("Can not create mio::net::TcpListener"));
+                            Command::Resume => if server.is_none() {
+                                // adopted socket is re-used, owned socket is re-created
+                                server = if let Some(lst) = paused.take() {
+                                    Some(lst)
+                                } else {
+                                    let lst = addr.listen(backlog)
+                                        .expect("Can not create listener");
+                                    Some(MioListener::new(lst)
+                                         .expect("Can not create mio listener"))
+                                };
 
                                 if let Some(ref server) = server {
                                     if let Err(err) = poll.register(
@@ -758,11 +2175,28 @@ fn start_accept_thread(sock: net::TcpListener, addr: net::SocketAddr, backlog: i
                                     }
                                 }
                             },
-                            Command::Stop => return,
-                        },
-                        Err(err) => match err {
-                            sync_mpsc::TryRecvError::Empty => (),
-                            sync_mpsc::TryRecvError::Disconnected => return,
+                            #[cfg(unix)]
+                            Command::Duplicate(tx) => {
+                                // socket is shared with other process now,
+                                // it can not be re-created or removed
+                                adopted = true;
+                                let res = if let Some(ref server) = server {
+                                    server.dup()
+                                } else if let Some(ref lst) = paused {
+                                    lst.dup()
+                                } else {
+                                    Err(io::Error::new(
+                                        io::ErrorKind::Other, "Server socket is closed"))
+                                };
+                                let _ = tx.send(res);
+                            },
+                            Command::Stop => {
+                                server.take();
+                                if !adopted {
+                                    addr.cleanup();
+                                }
+                                return
+                            },
                         }
                     },
                     _ => unreachable!(),
@@ -774,12 +2208,154 @@ fn start_accept_thread(sock: net::TcpListener, addr: net::SocketAddr, backlog: i
     (readiness, tx)
 }
 
-fn create_tcp_listener(addr: net::SocketAddr, backlog: i32) -> io::Result<net::TcpListener> {
+/// Get integer `SOL_SOCKET` level socket option
+#[cfg(unix)]
+fn get_sockopt(fd: RawFd, opt: libc::c_int) -> io::Result<libc::c_int> {
+    let mut val: libc::c_int = 0;
+    let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    if unsafe { libc::getsockopt(
+        fd, libc::SOL_SOCKET, opt, &mut val as *mut _ as *mut libc::c_void, &mut len) } != 0
+    {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(val)
+    }
+}
+
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub(crate) fn create_tcp_listener(addr: net::SocketAddr, backlog: i32, reuse_port: bool)
+                                  -> io::Result<net::TcpListener>
+{
     let builder = match addr {
         net::SocketAddr::V4(_) => TcpBuilder::new_v4()?,
         net::SocketAddr::V6(_) => TcpBuilder::new_v6()?,
     };
+    #[cfg(unix)]
+    {
+        if reuse_port {
+            builder.reuse_port(true)?;
+        }
+    }
     builder.bind(addr)?;
     builder.reuse_address(true)?;
     Ok(builder.listen(backlog)?)
 }
+
+#[cfg(unix)]
+fn create_uds_listener(path: &Path, backlog: i32, mode: Option<u32>)
+                       -> io::Result<unix::UnixListener>
+{
+    // remove stale socket file left by a previous process
+    if let Ok(meta) = fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display())))
+        }
+        if unix::UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is in use by another process", path.display())))
+        }
+        fs::remove_file(path)?;
+    }
+
+    // socket file is created with permissions not looser than `mode`,
+    // restrictive umask is set only for bind() call
+    let lst = if let Some(mode) = mode {
+        let mask = (!mode & 0o777) as libc::mode_t;
+        let prev = unsafe { libc::umask(mask) };
+        unsafe { libc::umask(prev | mask) };
+        let res = unix::UnixListener::bind(path);
+        unsafe { libc::umask(prev) };
+        let lst = res?;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+        lst
+    } else {
+        unix::UnixListener::bind(path)?
+    };
+    // std listener uses fixed backlog, listen() on bound socket updates it
+    if unsafe { libc::listen(lst.as_raw_fd(), backlog) } != 0 {
+        return Err(io::Error::last_os_error())
+    }
+    Ok(lst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dispatcher(dispatch: Dispatch, loads: &[usize]) -> Dispatcher {
+        let workers = loads.iter().map(|load| {
+            let (tx, _) = mpsc::unbounded();
+            WorkerClient{tx: tx, load: Arc::new(AtomicUsize::new(*load))}
+        }).collect();
+        Dispatcher::new(dispatch, None, workers)
+    }
+
+    #[test]
+    fn test_dispatch_round_robin() {
+        let mut d = dispatcher(Dispatch::RoundRobin, &[5, 0, 0]);
+        assert_eq!(d.select(None), 0);
+        assert_eq!(d.select(None), 1);
+        assert_eq!(d.select(None), 2);
+        assert_eq!(d.select(None), 0);
+    }
+
+    #[test]
+    fn test_dispatch_least_connections() {
+        let mut d = dispatcher(Dispatch::LeastConnections, &[5, 1, 3]);
+        assert_eq!(d.select(None), 1);
+        assert_eq!(d.select(None), 1);
+
+        // equally loaded workers get connections in turn
+        let mut d = dispatcher(Dispatch::LeastConnections, &[0, 0, 0]);
+        assert_eq!(d.select(None), 0);
+        assert_eq!(d.select(None), 1);
+        assert_eq!(d.select(None), 2);
+    }
+
+    #[test]
+    fn test_dispatch_peer_hash() {
+        let mut d = dispatcher(Dispatch::PeerHash, &[0, 0, 0, 0]);
+        let peer1: net::SocketAddr = "10.0.0.1:1000".parse().unwrap();
+        let peer2: net::SocketAddr = "10.0.0.1:2000".parse().unwrap();
+        let idx = d.select(Some(&peer1));
+        assert_eq!(d.select(Some(&peer2)), idx);
+        assert_eq!(d.select(Some(&peer1)), idx);
+    }
+
+    #[test]
+    fn test_dispatch_max_connections() {
+        let mut d = dispatcher(Dispatch::RoundRobin, &[10, 4, 9]);
+        d.maxconn = Some(10);
+        assert!(!d.is_full());
+        assert!(d.is_available());
+
+        // full worker is skipped
+        assert_eq!(d.select(None), 1);
+        assert_eq!(d.select(None), 2);
+        assert_eq!(d.select(None), 1);
+
+        d.workers[1].load.store(10, Ordering::Relaxed);
+        d.workers[2].load.store(10, Ordering::Relaxed);
+        assert!(d.is_full());
+        assert!(!d.is_available());
+
+        d.workers[2].load.store(9, Ordering::Relaxed);
+        assert!(!d.is_full());
+        assert!(!d.is_available());
+        d.workers[2].load.store(8, Ordering::Relaxed);
+        assert!(d.is_available());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_uds_mode() {
+        let path = env::temp_dir().join(format!("actix-web-mode-{}.sock", process::id()));
+        let _lst = create_uds_listener(&path, 16, Some(0o600)).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = fs::remove_file(&path);
+    }
+}
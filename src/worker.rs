@@ -1,31 +1,46 @@
-use std::{net, time};
+use std::{io, net, time};
 use std::rc::Rc;
 use std::cell::{Cell, RefCell, RefMut};
-use futures::Future;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use futures::{future, Async, Future, Poll};
+use futures::task::{self, Task};
 use futures::unsync::oneshot;
-use tokio_core::net::TcpStream;
-use tokio_core::reactor::Handle;
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_core::net::{TcpListener, TcpStream};
+use tokio_core::reactor::{Handle, Timeout};
 use net2::TcpStreamExt;
 
-#[cfg(feature="tls")]
-use futures::future;
+#[cfg(unix)]
+use std::os::unix::net as unix;
+#[cfg(unix)]
+use tokio_uds::UnixStream;
+
 #[cfg(feature="tls")]
 use native_tls::TlsAcceptor;
 #[cfg(feature="tls")]
 use tokio_tls::TlsAcceptorExt;
 
 #[cfg(feature="alpn")]
-use futures::future;
+use std::sync::RwLock;
 #[cfg(feature="alpn")]
 use openssl::ssl::SslAcceptor;
 #[cfg(feature="alpn")]
 use tokio_openssl::SslAcceptorExt;
+#[cfg(feature="alpn")]
+use info::PeerCertificate;
 
 use actix::*;
 use actix::msgs::StopArbiter;
 
 use helpers;
-use channel::{HttpChannel, HttpHandler};
+use application::HttpApplication;
+use channel::{HttpChannel, HttpHandler, Connection, ConnectHandler};
+use server::{connection_error, create_tcp_listener};
+use proxy::ProxyProtocol;
+use h1::Http1Limits;
+use h2::Http2Settings;
 
 
 #[derive(Message)]
@@ -33,6 +48,17 @@ pub(crate) struct Conn<T> {
     pub io: T,
     pub peer: Option<net::SocketAddr>,
     pub http2: bool,
+    /// Index of listener's application group
+    pub group: usize,
+    /// Plain http connection of redirecting listener
+    pub redirect: bool,
+}
+
+/// Accepted connection socket
+pub(crate) enum StdStream {
+    Tcp(net::TcpStream),
+    #[cfg(unix)]
+    Unix(unix::UnixStream),
 }
 
 /// Stop worker message. Returns `true` on successful shutdown
@@ -43,6 +69,14 @@ pub(crate) struct StopWorker {
     pub graceful: Option<time::Duration>,
 }
 
+/// Stop accepting connections on `SO_REUSEPORT` listeners
+#[derive(Message)]
+pub(crate) struct PauseWorker;
+
+/// Re-create `SO_REUSEPORT` listeners and resume accepting connections
+#[derive(Message)]
+pub(crate) struct ResumeWorker;
+
 pub(crate) struct WorkerSettings<H> {
     h: RefCell<Vec<H>>,
     enabled: bool,
@@ -50,6 +84,18 @@ pub(crate) struct WorkerSettings<H> {
     bytes: Rc<helpers::SharedBytesPool>,
     messages: Rc<helpers::SharedMessagePool>,
     channels: Cell<usize>,
+    tasks: RefCell<HashMap<usize, Task>>,
+    next_task: Cell<usize>,
+    shutdown: Cell<bool>,
+    proxy_protocol: bool,
+    secure: bool,
+    limits: Http1Limits,
+    h2: Http2Settings,
+    client_timeout: u64,
+    min_body_rate: usize,
+    write_timeout: u64,
+    max_requests: usize,
+    on_connect: Option<ConnectHandler>,
 }
 
 impl<H> WorkerSettings<H> {
@@ -61,9 +107,71 @@ impl<H> WorkerSettings<H> {
             bytes: Rc::new(helpers::SharedBytesPool::new()),
             messages: Rc::new(helpers::SharedMessagePool::new()),
             channels: Cell::new(0),
+            tasks: RefCell::new(HashMap::new()),
+            next_task: Cell::new(0),
+            shutdown: Cell::new(false),
+            proxy_protocol: false,
+            secure: false,
+            limits: Http1Limits::default(),
+            h2: Http2Settings::default(),
+            client_timeout: 0,
+            min_body_rate: 0,
+            write_timeout: 0,
+            max_requests: 0,
+            on_connect: None,
         }
     }
 
+    /// Set callback for accepted connections
+    pub(crate) fn on_connect(mut self, f: Option<ConnectHandler>) -> Self {
+        self.on_connect = f;
+        self
+    }
+
+    /// Set response write timeout in seconds, zero disables timeout
+    pub(crate) fn write_timeout(mut self, timeout: u64) -> Self {
+        self.write_timeout = timeout;
+        self
+    }
+
+    /// Set maximum number of requests per connection, zero means no limit
+    pub(crate) fn max_requests(mut self, num: usize) -> Self {
+        self.max_requests = num;
+        self
+    }
+
+    /// Set request head timeout in milliseconds and minimum payload rate
+    /// in bytes per second, zero disables check
+    pub(crate) fn client_timeouts(mut self, timeout: u64, min_body_rate: usize) -> Self {
+        self.client_timeout = timeout;
+        self.min_body_rate = min_body_rate;
+        self
+    }
+
+    /// Set http/1 parser limits
+    pub(crate) fn h1_limits(mut self, limits: Http1Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Set http/2 connection settings
+    pub(crate) fn h2_settings(mut self, settings: Http2Settings) -> Self {
+        self.h2 = settings;
+        self
+    }
+
+    /// Expect PROXY protocol header on accepted connections
+    pub(crate) fn proxy_protocol(mut self, enable: bool) -> Self {
+        self.proxy_protocol = enable;
+        self
+    }
+
+    /// Connections are encrypted with tls
+    pub(crate) fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
     pub fn handlers(&self) -> RefMut<Vec<H>> {
         self.h.borrow_mut()
     }
@@ -73,6 +181,30 @@ impl<H> WorkerSettings<H> {
     pub fn keep_alive_enabled(&self) -> bool {
         self.enabled
     }
+    pub fn limits(&self) -> &Http1Limits {
+        &self.limits
+    }
+    pub fn h2_settings(&self) -> &Http2Settings {
+        &self.h2
+    }
+    pub fn is_secure(&self) -> bool {
+        self.secure
+    }
+    pub fn client_timeout(&self) -> u64 {
+        self.client_timeout
+    }
+    pub fn min_body_rate(&self) -> usize {
+        self.min_body_rate
+    }
+    pub fn get_write_timeout(&self) -> u64 {
+        self.write_timeout
+    }
+    pub fn get_max_requests(&self) -> usize {
+        self.max_requests
+    }
+    pub fn connect_handler(&self) -> Option<&ConnectHandler> {
+        self.on_connect.as_ref()
+    }
     pub fn get_shared_bytes(&self) -> helpers::SharedBytes {
         helpers::SharedBytes::new(self.bytes.get_bytes(), Rc::clone(&self.bytes))
     }
@@ -90,29 +222,78 @@ impl<H> WorkerSettings<H> {
             error!("Number of removed channels is bigger than added channel. Bug in actix-web");
         }
     }
+
+    /// Register current task for shutdown notification
+    pub fn register_task(&self) -> usize {
+        let id = self.next_task.get();
+        self.next_task.set(id.wrapping_add(1));
+        self.tasks.borrow_mut().insert(id, task::current());
+        id
+    }
+    pub fn unregister_task(&self, id: usize) {
+        self.tasks.borrow_mut().remove(&id);
+    }
+
+    /// Server is shutting down, connections should not accept new requests
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.get()
+    }
+
+    /// Notify all live channels about server shutdown
+    pub fn shutdown(&self) {
+        self.shutdown.set(true);
+        for task in self.tasks.borrow().values() {
+            task.notify();
+        }
+    }
 }
 
 /// Http worker
 ///
 /// Worker accepts Socket objects via unbounded channel and start requests processing.
+/// Every application group has its own settings, group is selected by listener.
 pub(crate) struct Worker<H> {
-    h: Rc<WorkerSettings<H>>,
+    h: Vec<Rc<WorkerSettings<H>>>,
+    redirect: Option<Rc<WorkerSettings<HttpApplication>>>,
     hnd: Handle,
     handler: StreamHandlerType,
+    load: Arc<AtomicUsize>,
+    reuse_port: Vec<(net::SocketAddr, usize)>,
+    backlog: i32,
+    maxconn: Option<usize>,
+    accept: Vec<oneshot::Sender<()>>,
 }
 
 impl<H: 'static> Worker<H> {
 
-    pub(crate) fn new(h: Vec<H>, handler: StreamHandlerType, keep_alive: Option<u64>)
-                      -> Worker<H>
+    pub(crate) fn new(settings: Vec<WorkerSettings<H>>, handler: StreamHandlerType,
+                      load: Arc<AtomicUsize>) -> Worker<H>
     {
         Worker {
-            h: Rc::new(WorkerSettings::new(h, keep_alive)),
+            h: settings.into_iter().map(Rc::new).collect(),
+            redirect: None,
             hnd: Arbiter::handle().clone(),
             handler: handler,
+            load: load,
+            reuse_port: Vec::new(),
+            backlog: 0,
+            maxconn: None,
+            accept: Vec::new(),
         }
     }
 
+    /// Handle connections of redirecting listeners with application
+    pub(crate) fn redirect(mut self, settings: Option<WorkerSettings<HttpApplication>>) -> Self {
+        self.redirect = settings.map(Rc::new);
+        self
+    }
+
+    /// Number of open connections of all application groups
+    fn channels(&self) -> usize {
+        let num = self.redirect.as_ref().map(|h| h.channels.get()).unwrap_or(0);
+        self.h.iter().fold(num, |num, h| num + h.channels.get())
+    }
+
     fn update_time(&self, ctx: &mut Context<Self>) {
         helpers::update_date();
         ctx.run_later(time::Duration::new(1, 0), |slf, ctx| slf.update_time(ctx));
@@ -122,7 +303,7 @@ impl<H: 'static> Worker<H> {
                         tx: oneshot::Sender<bool>, dur: time::Duration) {
         // sleep for 1 second and then check again
         ctx.run_later(time::Duration::new(1, 0), move |slf, ctx| {
-            let num = slf.h.channels.get();
+            let num = slf.channels();
             if num == 0 {
                 let _ = tx.send(true);
                 Arbiter::arbiter().send(StopArbiter(0));
@@ -137,6 +318,50 @@ impl<H: 'static> Worker<H> {
     }
 }
 
+impl<H: HttpHandler + 'static> Worker<H> {
+
+    /// Accept connections on worker's own `SO_REUSEPORT` listeners
+    pub(crate) fn reuse_port(mut self,
+                             listeners: Vec<(net::SocketAddr, net::TcpListener, usize)>,
+                             backlog: i32, maxconn: Option<usize>) -> Self
+    {
+        self.backlog = backlog;
+        self.maxconn = maxconn;
+        for (addr, lst, group) in listeners {
+            self.reuse_port.push((addr, group));
+            if let Err(err) = self.start_accept(addr, lst, group) {
+                error!("Can not start accepting connections on {}: {}", addr, err);
+            }
+        }
+        self
+    }
+
+    fn start_accept(&mut self, addr: net::SocketAddr,
+                    lst: net::TcpListener, group: usize) -> io::Result<()>
+    {
+        let h = match self.h.get(group) {
+            Some(h) => Rc::clone(h),
+            None => return Err(io::Error::new(io::ErrorKind::Other, "Unknown application group")),
+        };
+        let lst = TcpListener::from_listener(lst, &addr, &self.hnd)?;
+        let (tx, rx) = oneshot::channel();
+        self.accept.push(tx);
+        self.hnd.spawn(Accept {
+            lst: lst,
+            addr: addr,
+            stop: rx,
+            h: h,
+            hnd: self.hnd.clone(),
+            handler: self.handler.clone(),
+            load: Arc::clone(&self.load),
+            maxconn: self.maxconn,
+            timer: None,
+            backoff: None,
+        });
+        Ok(())
+    }
+}
+
 impl<H: 'static> Actor for Worker<H> {
     type Context = Context<Self>;
 
@@ -145,21 +370,65 @@ impl<H: 'static> Actor for Worker<H> {
     }
 }
 
-impl<H> StreamHandler<Conn<net::TcpStream>> for Worker<H>
+impl<H> StreamHandler<Conn<StdStream>> for Worker<H>
     where H: HttpHandler + 'static {}
 
-impl<H> Handler<Conn<net::TcpStream>> for Worker<H>
+impl<H> Handler<Conn<StdStream>> for Worker<H>
     where H: HttpHandler + 'static,
 {
-    fn handle(&mut self, msg: Conn<net::TcpStream>, _: &mut Context<Self>)
-              -> Response<Self, Conn<net::TcpStream>>
+    fn handle(&mut self, msg: Conn<StdStream>, _: &mut Context<Self>)
+              -> Response<Self, Conn<StdStream>>
     {
-        if !self.h.keep_alive_enabled() &&
-            msg.io.set_keepalive(Some(time::Duration::new(75, 0))).is_err()
-        {
-            error!("Can not set socket keep-alive option");
+        if msg.redirect {
+            if let Some(ref h) = self.redirect {
+                set_keepalive(&msg.io, h);
+                // redirecting listener does not use tls
+                StreamHandlerType::Normal.handle(
+                    Rc::clone(h), &self.hnd, Arc::clone(&self.load), msg);
+            } else {
+                self.load.fetch_sub(1, Ordering::Relaxed);
+            }
+            return Self::empty()
+        }
+
+        let h = if let Some(h) = self.h.get(msg.group) {
+            Rc::clone(h)
+        } else {
+            error!("Unknown application group: {}", msg.group);
+            self.load.fetch_sub(1, Ordering::Relaxed);
+            return Self::empty()
+        };
+        set_keepalive(&msg.io, &h);
+        self.handler.handle(h, &self.hnd, Arc::clone(&self.load), msg);
+        Self::empty()
+    }
+}
+
+impl<H> Handler<PauseWorker> for Worker<H>
+    where H: HttpHandler + 'static,
+{
+    fn handle(&mut self, _: PauseWorker, _: &mut Context<Self>) -> Response<Self, PauseWorker>
+    {
+        // dropped sender stops accept future and closes listener
+        self.accept.clear();
+        Self::empty()
+    }
+}
+
+impl<H> Handler<ResumeWorker> for Worker<H>
+    where H: HttpHandler + 'static,
+{
+    fn handle(&mut self, _: ResumeWorker, _: &mut Context<Self>) -> Response<Self, ResumeWorker>
+    {
+        if self.accept.is_empty() {
+            for (addr, group) in self.reuse_port.clone() {
+                let res = create_tcp_listener(addr, self.backlog, true)
+                    .and_then(|lst| self.start_accept(addr, lst, group));
+                if let Err(err) = res {
+                    error!("Can not resume accepting connections on {}: {}", addr, err);
+                }
+            }
         }
-        self.handler.handle(Rc::clone(&self.h), &self.hnd, msg);
         Self::empty()
     }
 }
@@ -170,12 +439,21 @@ impl<H> Handler<StopWorker> for Worker<H>
 {
     fn handle(&mut self, msg: StopWorker, ctx: &mut Context<Self>) -> Response<Self, StopWorker>
     {
-        let num = self.h.channels.get();
+        // stop accepting connections
+        self.accept.clear();
+
+        let num = self.channels();
         if num == 0 {
             info!("Shutting down http worker, 0 connections");
             Self::reply(true)
         } else if let Some(dur) = msg.graceful {
             info!("Graceful http worker shutdown, {} connections", num);
+            for h in &self.h {
+                h.shutdown();
+            }
+            if let Some(ref h) = self.redirect {
+                h.shutdown();
+            }
             let (tx, rx) = oneshot::channel();
             self.shutdown_timeout(ctx, tx, dur);
             Self::async_reply(rx.map_err(|_| ()).actfuture())
@@ -186,53 +464,131 @@ impl<H> Handler<StopWorker> for Worker<H>
     }
 }
 
+/// Set tcp keep-alive if http keep-alive is disabled
+fn set_keepalive<H>(io: &StdStream, h: &WorkerSettings<H>) {
+    match *io {
+        StdStream::Tcp(ref io) => {
+            if !h.keep_alive_enabled() &&
+                io.set_keepalive(Some(time::Duration::new(75, 0))).is_err()
+            {
+                error!("Can not set socket keep-alive option");
+            }
+        }
+        // unix sockets do not support keep-alive option
+        #[cfg(unix)]
+        StdStream::Unix(_) => (),
+    }
+}
+
+/// PROXY protocol header timeout in milliseconds if client timeout is disabled
+const PROXY_HEADER_TIMEOUT: u64 = 5000;
+
 #[derive(Clone)]
 pub(crate) enum StreamHandlerType {
     Normal,
     #[cfg(feature="tls")]
     Tls(TlsAcceptor),
+    /// Acceptor is shared with server, certificate can be replaced at runtime
     #[cfg(feature="alpn")]
-    Alpn(SslAcceptor),
+    Alpn(Arc<RwLock<SslAcceptor>>),
 }
 
 impl StreamHandlerType {
 
-    fn handle<H: HttpHandler>(&mut self,
-                              h: Rc<WorkerSettings<H>>,
-                              hnd: &Handle, msg: Conn<net::TcpStream>) {
+    /// Connections are encrypted with tls
+    pub fn is_secure(&self) -> bool {
         match *self {
-            StreamHandlerType::Normal => {
-                let io = TcpStream::from_stream(msg.io, hnd)
+            StreamHandlerType::Normal => false,
+            #[cfg(feature="tls")]
+            StreamHandlerType::Tls(_) => true,
+            #[cfg(feature="alpn")]
+            StreamHandlerType::Alpn(_) => true,
+        }
+    }
+
+    /// Start connection processing. Connection is counted in `load`
+    /// by accept thread, it gets released when connection is closed.
+    fn handle<H: HttpHandler>(&mut self,
+                              h: Rc<WorkerSettings<H>>, hnd: &Handle,
+                              load: Arc<AtomicUsize>, msg: Conn<StdStream>) {
+        let Conn { io, peer, http2, .. } = msg;
+        match io {
+            StdStream::Tcp(io) => {
+                let io = TcpStream::from_stream(io, hnd)
                     .expect("failed to associate TCP stream");
+                self.spawn(h, hnd, load, io, peer, http2);
+            }
+            #[cfg(unix)]
+            StdStream::Unix(io) => {
+                let io = UnixStream::from_stream(io, hnd)
+                    .expect("failed to associate unix stream");
+                // unix domain socket peer does not have ip address
+                self.spawn(h, hnd, load, io, None, http2);
+            }
+        }
+    }
+
+    fn spawn<H, T>(&mut self, h: Rc<WorkerSettings<H>>, hnd: &Handle,
+                   load: Arc<AtomicUsize>, io: T, peer: Option<net::SocketAddr>, http2: bool)
+        where H: HttpHandler + 'static, T: AsyncRead + AsyncWrite + 'static
+    {
+        let fut: Box<Future<Item=(), Error=()>> = if h.proxy_protocol {
+            // PROXY header precedes tls handshake, connection is counted
+            // as live channel while header is being read
+            let ms = if h.client_timeout > 0 { h.client_timeout } else { PROXY_HEADER_TIMEOUT };
+            let timeout = Timeout::new(time::Duration::from_millis(ms), hnd).unwrap()
+                .then(|_| Err::<(T, Option<net::SocketAddr>), _>(io::Error::new(
+                    io::ErrorKind::TimedOut, "Timeout during reading PROXY protocol header")));
+            let handler = self.clone();
+            h.add_channel();
+            Box::new(ProxyProtocol::new(io, peer).select(timeout).then(move |res| {
+                h.remove_channel();
+                match res.map(|(item, _)| item).map_err(|(err, _)| err) {
+                    Ok((io, peer)) => future::Either::A(handler.channel(h, io, peer, http2)),
+                    Err(err) => {
+                        trace!("Error during reading PROXY protocol header: {}", err);
+                        future::Either::B(future::ok(()))
+                    }
+                }
+            }))
+        } else {
+            self.channel(h, io, peer, http2)
+        };
+        hnd.spawn(fut.then(move |res| {
+            load.fetch_sub(1, Ordering::Relaxed);
+            res
+        }));
+    }
 
-                hnd.spawn(HttpChannel::new(h, io, msg.peer, msg.http2));
+    fn channel<H, T>(&self, h: Rc<WorkerSettings<H>>,
+                     io: T, peer: Option<net::SocketAddr>, http2: bool)
+                     -> Box<Future<Item=(), Error=()>>
+        where H: HttpHandler + 'static, T: AsyncRead + AsyncWrite + 'static
+    {
+        match *self {
+            StreamHandlerType::Normal => {
+                Box::new(HttpChannel::new(h, io, Connection::new(peer, http2)))
             }
             #[cfg(feature="tls")]
             StreamHandlerType::Tls(ref acceptor) => {
-                let Conn { io, peer, http2 } = msg;
-                let io = TcpStream::from_stream(io, hnd)
-                    .expect("failed to associate TCP stream");
-
-                hnd.spawn(
+                Box::new(
                     TlsAcceptorExt::accept_async(acceptor, io).then(move |res| {
                         match res {
-                            Ok(io) => Arbiter::handle().spawn(
-                                HttpChannel::new(h, io, peer, http2)),
-                            Err(err) =>
-                                trace!("Error during handling tls connection: {}", err),
-                        };
-                        future::result(Ok(()))
+                            Ok(io) => future::Either::A(
+                                HttpChannel::new(h, io, Connection::new(peer, http2))),
+                            Err(err) => {
+                                trace!("Error during handling tls connection: {}", err);
+                                future::Either::B(future::ok(()))
+                            }
+                        }
                     })
-                );
+                )
             }
             #[cfg(feature="alpn")]
             StreamHandlerType::Alpn(ref acceptor) => {
-                let Conn { io, peer, .. } = msg;
-                let io = TcpStream::from_stream(io, hnd)
-                    .expect("failed to associate TCP stream");
-
-                hnd.spawn(
-                    SslAcceptorExt::accept_async(acceptor, io).then(move |res| {
+                let acceptor = acceptor.read().unwrap().clone();
+                Box::new(
+                    SslAcceptorExt::accept_async(&acceptor, io).then(move |res| {
                         match res {
                             Ok(io) => {
                                 let http2 = if let Some(p) = io.get_ref().ssl().selected_alpn_protocol()
@@ -241,14 +597,115 @@ impl StreamHandlerType {
                                 } else {
                                     false
                                 };
-                                Arbiter::handle().spawn(HttpChannel::new(h, io, peer, http2));
+                                // verified client certificate
+                                let cert = io.get_ref().ssl().peer_certificate()
+                                    .map(|cert| PeerCertificate::from_x509(&cert));
+                                let name = io.get_ref().ssl().servername().map(|n| n.to_owned());
+                                let conn = Connection::new(peer, http2).tls(cert, name);
+                                future::Either::A(HttpChannel::new(h, io, conn))
                             },
-                            Err(err) =>
-                                trace!("Error during handling tls connection: {}", err),
-                        };
-                        future::result(Ok(()))
+                            Err(err) => {
+                                trace!("Error during handling tls connection: {}", err);
+                                future::Either::B(future::ok(()))
+                            }
+                        }
                     })
-                );
+                )
+            }
+        }
+    }
+}
+
+/// Accept loop of worker's `SO_REUSEPORT` listener
+struct Accept<H> {
+    lst: TcpListener,
+    addr: net::SocketAddr,
+    stop: oneshot::Receiver<()>,
+    h: Rc<WorkerSettings<H>>,
+    hnd: Handle,
+    handler: StreamHandlerType,
+    load: Arc<AtomicUsize>,
+    maxconn: Option<usize>,
+    timer: Option<Timeout>,
+    backoff: Option<Timeout>,
+}
+
+impl<H: HttpHandler + 'static> Accept<H> {
+    /// Check connections limit. Accepting stops when limit is reached
+    /// and resumes once number of connections drops by 10 percent.
+    fn is_full(&mut self) -> bool {
+        let max = if let Some(max) = self.maxconn { max } else { return false };
+        let load = self.load.load(Ordering::Relaxed);
+
+        if self.timer.is_some() {
+            if load < max - max / 10 {
+                self.timer = None;
+                info!("Resumed accepting connections on {}", self.addr);
+                return false
+            }
+        } else if load < max {
+            return false
+        } else {
+            info!("Connections limit is reached, stop accepting connections on {}", self.addr);
+        }
+
+        // worker does not notify about closed connections, check load periodically
+        let mut timer = Timeout::new(time::Duration::from_millis(100), &self.hnd).unwrap();
+        let _ = timer.poll();
+        self.timer = Some(timer);
+        true
+    }
+}
+
+impl<H: HttpHandler + 'static> Future for Accept<H> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        match self.stop.poll() {
+            Ok(Async::NotReady) => (),
+            _ => return Ok(Async::Ready(())),
+        }
+
+        loop {
+            if let Some(ref mut timer) = self.timer {
+                match timer.poll() {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    _ => (),
+                }
+            }
+            if let Some(mut backoff) = self.backoff.take() {
+                if let Ok(Async::NotReady) = backoff.poll() {
+                    self.backoff = Some(backoff);
+                    return Ok(Async::NotReady)
+                }
+            }
+            if self.is_full() {
+                continue
+            }
+
+            match self.lst.accept() {
+                Ok((io, peer)) => {
+                    if !self.h.keep_alive_enabled() &&
+                        io.set_keepalive(Some(time::Duration::new(75, 0))).is_err()
+                    {
+                        error!("Can not set socket keep-alive option");
+                    }
+                    self.load.fetch_add(1, Ordering::Relaxed);
+                    self.handler.spawn(Rc::clone(&self.h), &self.hnd,
+                                       Arc::clone(&self.load), io, Some(peer), false);
+                },
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock =>
+                    return Ok(Async::NotReady),
+                Err(ref err) if connection_error(err) => (),
+                Err(err) => {
+                    // i.e. EMFILE, retry after short delay
+                    error!("Error accepting connection on {}: {:?}", self.addr, err);
+                    match Timeout::new(time::Duration::from_millis(100), &self.hnd) {
+                        Ok(timeout) => self.backoff = Some(timeout),
+                        Err(_) => return Ok(Async::Ready(())),
+                    }
+                }
             }
         }
     }
@@ -3,11 +3,14 @@ extern crate actix_web;
 extern crate tokio_core;
 extern crate reqwest;
 extern crate futures;
+#[cfg(feature="alpn")]
+extern crate openssl;
 
 use std::{net, thread, time};
+use std::io::{Read, Write};
 use std::sync::{Arc, mpsc};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use futures::Future;
+use futures::{Future, Stream};
 
 use actix_web::*;
 use actix::System;
@@ -42,6 +45,150 @@ fn test_start() {
     assert!(reqwest::get(&format!("http://{}/", addr)).unwrap().status().is_success());
 }
 
+#[test]
+#[cfg(unix)]
+fn test_reuse_port() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))])
+            .threads(2)
+            .reuse_port(true)
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let srv_addr = srv.start();
+        let _ = tx.send((addr, srv_addr));
+        sys.run();
+    });
+    let (addr, srv_addr) = rx.recv().unwrap();
+    assert!(reqwest::get(&format!("http://{}/", addr)).unwrap().status().is_success());
+
+    // pause
+    let _ = srv_addr.call_fut(dev::PauseServer).wait();
+    thread::sleep(time::Duration::from_millis(100));
+    assert!(net::TcpStream::connect(addr).is_err());
+
+    // resume
+    let _ = srv_addr.call_fut(dev::ResumeServer).wait();
+    thread::sleep(time::Duration::from_millis(100));
+    assert!(reqwest::get(&format!("http://{}/", addr)).unwrap().status().is_success());
+}
+
+#[test]
+fn test_listen() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let lst = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))])
+            .listen(lst);
+        let addr = srv.addrs()[0];
+        let srv_addr = srv.start();
+        let _ = tx.send((addr, srv_addr));
+        sys.run();
+    });
+    let (addr, srv_addr) = rx.recv().unwrap();
+    assert!(reqwest::get(&format!("http://{}/", addr)).unwrap().status().is_success());
+
+    // pause, adopted socket stays open
+    let _ = srv_addr.call_fut(dev::PauseServer).wait();
+    thread::sleep(time::Duration::from_millis(100));
+    assert!(net::TcpStream::connect(addr).is_ok());
+
+    // resume
+    let _ = srv_addr.call_fut(dev::ResumeServer).wait();
+    assert!(reqwest::get(&format!("http://{}/", addr)).unwrap().status().is_success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_start_uds() {
+    use std::os::unix::net::UnixStream;
+
+    let path = std::env::temp_dir().join(
+        format!("actix-web-test-{}.sock", test::TestServer::unused_addr().port()));
+    let (tx, rx) = mpsc::channel();
+
+    let srv_path = path.clone();
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))]);
+        let srv_addr = srv.bind_uds(&srv_path).unwrap().start();
+        let _ = tx.send(srv_addr);
+        sys.run();
+    });
+    let srv_addr = rx.recv().unwrap();
+
+    let mut stream = UnixStream::connect(&path).unwrap();
+    stream.write_all(b"GET / HTTP/1.0\r\n\r\n").unwrap();
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.0 200 OK"));
+
+    // socket file is removed on stop
+    let _ = srv_addr.call_fut(dev::StopServer{graceful: false}).wait();
+    thread::sleep(time::Duration::from_millis(100));
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_handoff_reuse_port() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))])
+            .threads(1)
+            .reuse_port(true)
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let srv_addr = srv.start();
+        let _ = tx.send((addr, srv_addr));
+        sys.run();
+    });
+    let (addr, srv_addr) = rx.recv().unwrap();
+
+    // listeners are owned by workers, they can not be handed over
+    let msg = dev::HandoffServer{
+        program: "true".into(), args: Vec::new(), timeout: time::Duration::from_secs(1)};
+    assert!(srv_addr.call_fut(msg).wait().unwrap().is_err());
+    assert!(reqwest::get(&format!("http://{}/", addr)).unwrap().status().is_success());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_listen_fd_invalid() {
+    use std::io::ErrorKind;
+    use std::os::unix::io::AsRawFd;
+    use tokio_core::net::TcpStream;
+
+    let new_srv = || -> HttpServer<TcpStream, net::SocketAddr, _, _> {
+        HttpServer::new(|| vec![Application::new()])
+    };
+
+    // not a stream socket
+    let udp = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let err = new_srv().listen_fd(udp.as_raw_fd()).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // stream socket that does not accept connections
+    let lst = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = net::TcpStream::connect(lst.local_addr().unwrap()).unwrap();
+    let err = new_srv().listen_fd(stream.as_raw_fd()).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
 #[test]
 fn test_simple() {
     let srv = test::TestServer::new(|app| app.handler(httpcodes::HTTPOk));
@@ -100,3 +247,613 @@ fn test_middlewares() {
     assert_eq!(num2.load(Ordering::Relaxed), 1);
     assert_eq!(num3.load(Ordering::Relaxed), 1);
 }
+
+#[test]
+fn test_client_timeout() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))])
+            .client_timeout(100)
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    // incomplete request head
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 408"));
+}
+
+#[test]
+fn test_proxy_protocol_timeout() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))])
+            .proxy_protocol(true)
+            .client_timeout(100)
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    // PROXY header is never sent, server closes connection
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.set_read_timeout(Some(time::Duration::from_secs(5)));
+    let mut data = Vec::new();
+    assert!(stream.read_to_end(&mut data).is_ok());
+    assert!(data.is_empty());
+}
+
+#[test]
+fn test_max_requests_per_connection() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))])
+            .max_requests_per_connection(2)
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    // third pipelined request is not processed, connection gets closed
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert_eq!(data.matches("HTTP/1.1 200").count(), 2);
+    assert_eq!(data.matches("connection: close").count(), 1);
+}
+
+#[test]
+fn test_preload() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.f(|_| httpcodes::HTTPOk.build()
+                                           .preload("/style.css").preload("/app.js")
+                                           .finish()))])
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.0\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.contains("200 OK\r\n"));
+    let data = data.to_lowercase();
+    assert!(data.contains("link: </style.css>; rel=preload\r\n"));
+    assert!(data.contains("link: </app.js>; rel=preload\r\n"));
+}
+
+#[test]
+fn test_expect_continue() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![
+                Application::new()
+                    .prefix("/upload")
+                    .resource("/", |r| r.f(|mut req| {
+                        req.payload_mut().readany().concat2()
+                            .from_err()
+                            .and_then(|body| Ok(httpcodes::HTTPOk.build().body(body)?))
+                            .responder()
+                    })),
+                Application::new()
+                    .prefix("/reject")
+                    .expect(|_| Err(error::ErrorUnauthorized("unauthorized").into()))
+                    .resource("/", |r| r.h(httpcodes::HTTPOk))])
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    // payload is sent after `100 Continue`
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(
+        b"PUT /upload/ HTTP/1.1\r\nExpect: 100-continue\r\n\
+          Content-Length: 4\r\nConnection: close\r\n\r\n");
+    let mut buf = [0u8; 25];
+    let _ = stream.read_exact(&mut buf);
+    assert_eq!(&buf[..], b"HTTP/1.1 100 Continue\r\n\r\n");
+
+    let _ = stream.write_all(b"data");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK"));
+    assert!(data.ends_with("data"));
+
+    // rejected upload, payload is never requested
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(
+        b"PUT /reject/ HTTP/1.1\r\nExpect: 100-continue\r\nContent-Length: 4\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 401 Unauthorized"));
+    assert!(!data.contains("100 Continue"));
+}
+
+#[test]
+fn test_on_connect() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let num = Arc::new(AtomicUsize::new(0));
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.f(|mut req| {
+                        req.extensions().get::<String>().cloned().unwrap_or_default()
+                    }))])
+            .on_connect(move |conn| {
+                // second connection is rejected
+                if num.fetch_add(1, Ordering::Relaxed) == 0 {
+                    conn.insert("connection data".to_owned());
+                    true
+                } else {
+                    false
+                }
+            })
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert_eq!(data.matches("connection data").count(), 2);
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.is_empty());
+}
+
+#[test]
+fn test_bind_with() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::with_state("main")
+                    .resource("/", |r| r.f(|req| *req.state()))])
+            .bind("127.0.0.1:0").unwrap()
+            .bind_with("127.0.0.1:0", || vec![Application::new()
+                    .resource("/", |r| r.f(|_| "admin"))]).unwrap();
+        let addrs = srv.addrs();
+        let srv_addr = srv.start();
+        let _ = tx.send((addrs, srv_addr));
+        sys.run();
+    });
+    let (addrs, srv_addr) = rx.recv().unwrap();
+    let get = |addr| {
+        let mut stream = net::TcpStream::connect(addr).unwrap();
+        let _ = stream.write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+        let mut data = String::new();
+        let _ = stream.read_to_string(&mut data);
+        data
+    };
+    assert!(get(addrs[0]).ends_with("main"));
+    assert!(get(addrs[1]).ends_with("admin"));
+
+    // pause stops all listeners
+    let _ = srv_addr.call_fut(dev::PauseServer).wait();
+    thread::sleep(time::Duration::from_millis(100));
+    assert!(net::TcpStream::connect(addrs[0]).is_err());
+    assert!(net::TcpStream::connect(addrs[1]).is_err());
+
+    // accept thread re-binds listeners asynchronously
+    let _ = srv_addr.call_fut(dev::ResumeServer).wait();
+    for _ in 0..50 {
+        if net::TcpStream::connect(addrs[1]).is_ok() {
+            break
+        }
+        thread::sleep(time::Duration::from_millis(20));
+    }
+    assert!(get(addrs[1]).ends_with("admin"));
+}
+
+#[test]
+#[cfg(feature="alpn")]
+fn test_redirect_http_to_https() {
+    let dir = std::env::temp_dir().join(
+        format!("actix-web-acme-{}", test::TestServer::unused_addr().port()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::File::create(dir.join("token")).unwrap().write_all(b"challenge").unwrap();
+    let (tx, rx) = mpsc::channel();
+
+    let acme = dir.clone();
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.h(httpcodes::HTTPOk))])
+            .bind("127.0.0.1:0").unwrap()
+            .redirect_http_to_https("127.0.0.1:0").unwrap()
+            .acme_challenge(acme);
+        let addr = srv.addrs()[1];
+        let _srv_addr = srv.start_ssl(&ssl_identity("server")).unwrap();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(
+        b"GET /index.html?q=1 HTTP/1.1\r\nHost: example.com:8080\r\nConnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 301"));
+    assert!(data.to_lowercase().contains("location: https://example.com/index.html?q=1\r\n"));
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.write_all(
+        b"GET /.well-known/acme-challenge/token HTTP/1.1\r\nConnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200"));
+    assert!(data.ends_with("challenge"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+#[should_panic(expected = "requires start_ssl()")]
+fn test_redirect_http_to_https_without_tls() {
+    let _ = HttpServer::new(|| vec![Application::new()])
+        .bind("127.0.0.1:0").unwrap()
+        .redirect_http_to_https("127.0.0.1:0").unwrap()
+        .start();
+}
+
+/// Read http/2 frames as (type, flags, stream id, payload)
+/// until HEADERS frame of stream 1 is received
+fn read_h2_frames(stream: &mut net::TcpStream, mut buf: Vec<u8>)
+                  -> Vec<(u8, u8, u32, Vec<u8>)>
+{
+    let mut data = [0u8; 1024];
+    let mut frames = Vec::new();
+    loop {
+        while buf.len() >= 9 {
+            let len = (buf[0] as usize) << 16 | (buf[1] as usize) << 8 | buf[2] as usize;
+            if buf.len() < 9 + len {
+                break
+            }
+            let id = ((buf[5] as u32) << 24 | (buf[6] as u32) << 16 |
+                      (buf[7] as u32) << 8 | buf[8] as u32) & 0x7fff_ffff;
+            let rest = buf.split_off(9 + len);
+            frames.push((buf[3], buf[4], id, buf[9..].to_vec()));
+            buf = rest;
+        }
+        if frames.iter().any(|&(kind, _, id, _)| kind == 1 && id == 1) {
+            return frames
+        }
+        let size = stream.read(&mut data).unwrap();
+        assert!(size > 0);
+        buf.extend_from_slice(&data[..size]);
+    }
+}
+
+#[test]
+fn test_h2_payload_eof() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.f(|mut req| {
+                        req.payload_mut().readany().concat2()
+                            .from_err()
+                            .and_then(|body| Ok(httpcodes::HTTPOk.build().body(body)?))
+                            .responder()
+                    }))])
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.set_read_timeout(Some(time::Duration::from_secs(5)));
+
+    // client preface, `POST /` request with indexed header fields and payload,
+    // handler responds once payload is complete
+    let _ = stream.write_all(
+        b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00\
+          \x00\x00\x03\x01\x04\x00\x00\x00\x01\x83\x86\x84\
+          \x00\x00\x04\x00\x01\x00\x00\x00\x01data");
+
+    let frames = read_h2_frames(&mut stream, Vec::new());
+    let headers = frames.iter().find(|&&(kind, _, id, _)| kind == 1 && id == 1).unwrap();
+    assert_eq!(headers.3[0], 0x88);
+}
+
+#[test]
+fn test_h2_payload_trailers() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.f(|mut req| {
+                        let req2 = req.clone();
+                        req.payload_mut().readany().concat2()
+                            .from_err()
+                            .and_then(move |_| {
+                                let checksum = req2.trailers()
+                                    .and_then(|t| t.get("checksum").cloned());
+                                if checksum.as_ref().map(|v| v == "abc").unwrap_or(false) {
+                                    Ok(httpcodes::HTTPOk.response())
+                                } else {
+                                    Ok(httpcodes::HTTPBadRequest.response())
+                                }
+                            })
+                            .responder()
+                    }))])
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.set_read_timeout(Some(time::Duration::from_secs(5)));
+
+    // client preface, `POST /` request and payload without END_STREAM flag
+    let _ = stream.write_all(
+        b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00\
+          \x00\x00\x03\x01\x04\x00\x00\x00\x01\x83\x86\x84\
+          \x00\x00\x04\x00\x00\x00\x00\x00\x01data");
+    thread::sleep(time::Duration::from_millis(100));
+
+    // trailers arrive later, `checksum: abc` literal header field
+    let _ = stream.write_all(
+        b"\x00\x00\x0e\x01\x05\x00\x00\x00\x01\x00\x08checksum\x03abc");
+
+    let frames = read_h2_frames(&mut stream, Vec::new());
+    let headers = frames.iter().find(|&&(kind, _, id, _)| kind == 1 && id == 1).unwrap();
+    assert_eq!(headers.3[0], 0x88);
+}
+
+#[test]
+fn test_h2c_upgrade() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.method(Method::GET).h(httpcodes::HTTPOk))])
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.set_read_timeout(Some(time::Duration::from_secs(5)));
+    let _ = stream.write_all(
+        b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade, HTTP2-Settings\r\n\
+          Upgrade: h2c\r\nHTTP2-Settings: AAMAAABkAAQAAP__\r\n\r\n");
+
+    let mut buf = Vec::new();
+    let mut data = [0u8; 1024];
+    let pos = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4
+        }
+        let size = stream.read(&mut data).unwrap();
+        assert!(size > 0);
+        buf.extend_from_slice(&data[..size]);
+    };
+    assert!(buf.starts_with(b"HTTP/1.1 101 Switching Protocols\r\n"));
+
+    // client preface with empty SETTINGS frame
+    let _ = stream.write_all(
+        b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00");
+
+    let frames = read_h2_frames(&mut stream, buf.split_off(pos));
+
+    // server preface is SETTINGS frame
+    assert_eq!(frames[0].0, 4);
+    assert_eq!(frames[0].1 & 1, 0);
+    assert_eq!(frames[0].2, 0);
+
+    // response to upgrade request, `:status 200` is indexed field
+    let headers = frames.iter().find(|&&(kind, _, id, _)| kind == 1 && id == 1).unwrap();
+    assert_eq!(headers.3[0], 0x88);
+}
+
+#[cfg(feature="alpn")]
+fn ssl_identity(name: &str) -> openssl::pkcs12::ParsedPkcs12 {
+    let mut der = Vec::new();
+    let _ = std::fs::File::open(format!("tests/tls/{}.p12", name)).unwrap().read_to_end(&mut der);
+    openssl::pkcs12::Pkcs12::from_der(&der).unwrap().parse("12345").unwrap()
+}
+
+/// Start tls server which responds with common name of client certificate,
+/// `sni` host gets certificate of "other" identity
+#[cfg(feature="alpn")]
+fn start_ssl_server(auth: Option<ClientAuth>, sni: Option<&'static str>) -> net::SocketAddr {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let mut srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.f(|req| {
+                        let info = req.connection_info();
+                        format!("{}:{}",
+                                info.server_name().unwrap_or("-"),
+                                info.peer_certificate()
+                                .and_then(|cert| cert.common_name()).unwrap_or("-"))
+                    }))]);
+        if let Some(auth) = auth {
+            srv = srv.client_auth(auth);
+        }
+        if let Some(host) = sni {
+            srv = srv.ssl_identity(host, &ssl_identity("other")).unwrap();
+        }
+        let srv = srv.bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start_ssl(&ssl_identity("server")).unwrap();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    rx.recv().unwrap()
+}
+
+/// Tls request with optional client certificate, returns response body
+/// and common name of server certificate
+#[cfg(feature="alpn")]
+fn ssl_get(addr: net::SocketAddr, host: &str, client_cert: bool) -> Option<(String, String)> {
+    use openssl::ssl::{SslConnectorBuilder, SslMethod};
+    use openssl::x509::X509_FILETYPE_PEM;
+    use openssl::nid::COMMONNAME;
+
+    let mut builder = SslConnectorBuilder::new(SslMethod::tls()).unwrap();
+    builder.set_ca_file("tests/tls/ca.pem").unwrap();
+    if client_cert {
+        builder.set_certificate_file("tests/tls/client.pem", X509_FILETYPE_PEM).unwrap();
+        builder.set_private_key_file("tests/tls/client-key.pem", X509_FILETYPE_PEM).unwrap();
+    }
+    let stream = net::TcpStream::connect(addr).unwrap();
+    let _ = stream.set_read_timeout(Some(time::Duration::from_secs(5)));
+    let mut stream = builder.build().connect(host, stream).ok()?;
+    let name = stream.ssl().peer_certificate()?
+        .subject_name().entries_by_nid(COMMONNAME).next()?
+        .data().as_utf8().ok()?.to_string();
+
+    // with tls 1.3 rejected client certificate is reported after handshake
+    stream.write_all(b"GET / HTTP/1.0\r\n\r\n").ok()?;
+    let mut data = String::new();
+    stream.read_to_string(&mut data).ok()?;
+    let pos = data.find("\r\n\r\n")?;
+    Some((data[pos + 4..].to_owned(), name))
+}
+
+#[test]
+#[cfg(feature="alpn")]
+fn test_ssl_client_auth_optional() {
+    let addr = start_ssl_server(Some(ClientAuth::optional("tests/tls/ca.pem")), None);
+
+    assert_eq!(ssl_get(addr, "localhost", true).unwrap().0, "localhost:client");
+    assert_eq!(ssl_get(addr, "localhost", false).unwrap().0, "localhost:-");
+}
+
+#[test]
+#[cfg(feature="alpn")]
+fn test_ssl_client_auth_required() {
+    let addr = start_ssl_server(Some(ClientAuth::required("tests/tls/ca.pem")), None);
+
+    assert_eq!(ssl_get(addr, "localhost", true).unwrap().0, "localhost:client");
+    assert!(ssl_get(addr, "localhost", false).is_none());
+}
+
+#[test]
+#[cfg(feature="alpn")]
+fn test_ssl_server_name() {
+    use openssl::ssl::{SslConnectorBuilder, SslMethod};
+    use openssl::x509::X509_FILETYPE_PEM;
+
+    let addr = start_ssl_server(
+        Some(ClientAuth::required("tests/tls/ca.pem")), Some("example.org"));
+
+    assert_eq!(ssl_get(addr, "localhost", true).unwrap(),
+               ("localhost:client".to_owned(), "localhost".to_owned()));
+    assert_eq!(ssl_get(addr, "example.org", true).unwrap(),
+               ("example.org:client".to_owned(), "example.org".to_owned()));
+    assert!(ssl_get(addr, "example.org", false).is_none());
+
+    // server name context negotiates h2 as well
+    let mut builder = SslConnectorBuilder::new(SslMethod::tls()).unwrap();
+    builder.set_ca_file("tests/tls/ca.pem").unwrap();
+    builder.set_certificate_file("tests/tls/client.pem", X509_FILETYPE_PEM).unwrap();
+    builder.set_private_key_file("tests/tls/client-key.pem", X509_FILETYPE_PEM).unwrap();
+    builder.set_alpn_protocols(&[b"h2", b"http/1.1"]).unwrap();
+    let stream = net::TcpStream::connect(addr).unwrap();
+    let stream = builder.build().connect("example.org", stream).unwrap();
+    assert_eq!(stream.ssl().selected_alpn_protocol(), Some(&b"h2"[..]));
+}
+
+#[test]
+#[cfg(feature="alpn")]
+fn test_ssl_on_connect() {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = System::new("test");
+        let srv = HttpServer::new(
+            || vec![Application::new()
+                    .resource("/", |r| r.f(|req| {
+                        req.connection_info().server_name().unwrap_or("-").to_owned()
+                    }))])
+            .client_auth(ClientAuth::optional("tests/tls/ca.pem"))
+            .on_connect(|conn| {
+                conn.server_name() == Some("localhost") &&
+                    conn.peer_certificate().and_then(|cert| cert.common_name()) == Some("client")
+            })
+            .bind("127.0.0.1:0").unwrap();
+        let addr = srv.addrs()[0];
+        let _srv_addr = srv.start_ssl(&ssl_identity("server")).unwrap();
+        let _ = tx.send(addr);
+        sys.run();
+    });
+    let addr = rx.recv().unwrap();
+
+    // callback rejects connections without client certificate
+    assert_eq!(ssl_get(addr, "localhost", true).unwrap().0, "localhost");
+    assert!(ssl_get(addr, "localhost", false).is_none());
+}